@@ -1,5 +1,7 @@
 use std::{io, num::ParseIntError};
 
+use crate::Tag;
+
 /// Error type for the library
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -8,9 +10,15 @@ pub enum Error {
     DataTooShort { context: &'static str },
 
     /// Unsupported font format
-    #[error("Only TrueType fonts (TTF) are supported; OTF/CFF fonts are not supported")]
+    #[error(
+        "Unsupported font format; only TTF, CFF-flavored OTF, TTC and WOFF 1.0 fonts are supported"
+    )]
     UnsupportedFormat,
 
+    /// A font collection was given where only a single font can be stored
+    #[error("Font collections cannot be stored as WOFF 1.0")]
+    CollectionNotSupported,
+
     /// Table extends beyond data bounds
     #[error("Table extends beyond data")]
     TableOutOfBounds,
@@ -19,10 +27,46 @@ pub enum Error {
     #[error("Invalid glyph: {0}")]
     InvalidGlyph(&'static str),
 
+    /// The font cannot be subset
+    #[error("Cannot subset font: {0}")]
+    Subset(&'static str),
+
+    /// A table checksum or `head.checkSumAdjustment` does not match the font data
+    #[error("Checksum mismatch in '{tag}' table: expected {expected:#010x}, found {actual:#010x}")]
+    ChecksumMismatch { tag: Tag, expected: u32, actual: u32 },
+
+    /// Encode options that cannot be used together or are out of range
+    #[error("Invalid encode options: {0}")]
+    InvalidOptions(&'static str),
+
+    /// The table filter removed every table of a font
+    #[error("No tables left to encode after filtering")]
+    NoTables,
+
+    /// A string that is not a valid table tag
+    #[error("Invalid table tag '{0}': expected 1 to 4 printable ASCII characters")]
+    InvalidTag(String),
+
+    /// The encode was aborted by [`EncodeOptions::cancel`](crate::EncodeOptions::cancel)
+    #[error("Encoding was cancelled")]
+    Cancelled,
+
+    /// Malformed WOFF 1.0 input
+    #[error("Invalid WOFF data: {0}")]
+    InvalidWoff(&'static str),
+
+    /// Malformed WOFF2 input
+    #[error("Invalid WOFF2 data: {0}")]
+    InvalidWoff2(&'static str),
+
     /// Brotli compression failed
     #[error("Brotli compression failed: {0}")]
     Compression(String),
 
+    /// Brotli decompression failed
+    #[error("Brotli decompression failed: {0}")]
+    Decompression(String),
+
     /// Failed to parse integer
     #[error("Failed to parse integer")]
     ParseInt(#[from] ParseIntError),
@@ -1,5 +1,8 @@
 //! A Pure Rust library for compressing TTF fonts to WOFF2 format.
 //!
+//! TrueType (TTF), CFF-flavored OpenType (OTF) and TrueType collection (TTC) fonts are supported
+//! as input.
+//!
 //! ## Example
 //!
 //! ```no_run
@@ -9,9 +12,35 @@
 //! let woff2_data = encode(&ttf_data, BrotliQuality::default()).unwrap();
 //! std::fs::write("font.woff2", &woff2_data).unwrap();
 //! ```
+//!
+//! WOFF2 files can be decoded back into an SFNT font with [`decode`]:
+//!
+//! ```no_run
+//! let woff2_data = std::fs::read("font.woff2").unwrap();
+//! let ttf_data = ttf2woff2::decode(&woff2_data).unwrap();
+//! std::fs::write("font.ttf", &ttf_data).unwrap();
+//! ```
+//!
+//! For user agents without WOFF2 support, [`encode_woff1`] writes a zlib-compressed WOFF 1.0 file
+//! instead.
+//!
+//! [`EncodeCache`] keeps encoded files in a directory, so that unchanged fonts are not compressed
+//! again.
+//!
+//! With the `async` feature, `encode_async` encodes on Tokio's blocking thread pool, so that long
+//! encodes do not stall an async runtime.
 
 pub use error::Error;
-pub use woff2::{BrotliQuality, EncodeOptions, encode, encode_no_transform, encode_with_options};
+#[cfg(feature = "async")]
+pub use woff2::encode_async;
+pub use woff2::{
+    BrotliBlockSize, BrotliMode, BrotliQuality, BrotliWindow, CancelHook, Candidate,
+    CollectionFontInfo, CollectionInfo, EncodeCache, EncodeOptions, EncodeReport, GlyfStreams,
+    OptimizeOptions, Optimized, Progress, ProgressHook, Shard, ShardPlan, SubsetSpec, TableFilter,
+    TableInfo, TableReport, Tag, Woff2Info, decode, decode_metadata, decode_private_data, encode,
+    encode_no_transform, encode_optimized, encode_shards, encode_to, encode_with_options,
+    encode_with_report, encode_woff1, font_face_css,
+};
 
 mod error;
 mod woff2;
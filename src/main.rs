@@ -1,28 +1,110 @@
 use std::{
-    fs::{read, write},
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    fs::{create_dir_all, read, read_to_string, write},
+    io::{self, Read, Write},
     num::NonZeroUsize,
-    path::PathBuf,
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
     process::ExitCode,
-    thread::available_parallelism,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{self, available_parallelism},
+    time::{Duration, Instant},
 };
 
-use clap::Parser;
-use ttf2woff2::{BrotliQuality, EncodeOptions, encode_with_options};
+use clap::{Parser, Subcommand, ValueEnum};
+use glob::glob;
+use serde_json::json;
+use ttf2woff2::{
+    BrotliBlockSize, BrotliMode, BrotliQuality, BrotliWindow, Candidate, EncodeCache,
+    EncodeOptions, EncodeReport, GlyfStreams, OptimizeOptions, ShardPlan, SubsetSpec, TableFilter,
+    TableReport, Tag, Woff2Info, encode_optimized, encode_with_options, encode_with_report,
+    encode_woff1, font_face_css,
+};
+use walkdir::WalkDir;
+
+/// Extensions of the files picked up when an input is a directory
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "woff"];
 
 #[derive(Parser)]
-#[command(about, version)]
+#[command(about, version, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    /// Path to the input TTF file
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    encode: EncodeArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the header and table directory of a WOFF2 file
+    Info(InfoArgs),
+}
+
+#[derive(clap::Args)]
+struct InfoArgs {
+    /// Path to the WOFF2 file
     input: PathBuf,
 
-    /// Path to the output WOFF2 file (defaults to input with .woff2 extension)
-    #[arg(short, long)]
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// The order in which `--shard` fills shards with characters.
+#[derive(Clone, Copy, ValueEnum)]
+enum ShardOrder {
+    /// Most frequently used characters first, by a built-in ranking of Latin, kana and CJK
+    Frequency,
+    /// Ascending code points
+    Codepoint,
+}
+
+#[derive(clap::Args)]
+struct EncodeArgs {
+    /// Input TTF, OTF, TTC or WOFF files, directories (searched recursively) or glob patterns; `-`
+    /// reads a single font from stdin
+    #[arg(required = true, value_name = "INPUT")]
+    inputs: Vec<PathBuf>,
+
+    /// Path to the output WOFF2 file, or `-` for stdout (defaults to input with .woff2 extension,
+    /// .woff with --woff, or stdout when reading from stdin); only valid with a single input file
+    #[arg(short, long, conflicts_with = "out_dir")]
     output: Option<PathBuf>,
 
+    /// Directory to write WOFF2 files to, mirroring the layout of the inputs
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Number of files to convert in parallel: 0=all cores, N=N files at a time
+    #[arg(short, long, default_value = "0")]
+    jobs: usize,
+
     /// Brotli compression quality (0-11)
     #[arg(short, long, default_value = "9")]
     quality: u8,
 
+    /// Brotli window size as a power of two (10-24). 24 (16 MiB) often compresses fonts of
+    /// several megabytes, such as CJK fonts, noticeably better
+    #[arg(long, value_name = "N", default_value = "22")]
+    lgwin: BrotliWindow,
+
+    /// Brotli maximum input block size as a power of two (16-24), or auto to let the encoder
+    /// choose
+    #[arg(long, value_name = "N", default_value = "auto")]
+    lgblock: BrotliBlockSize,
+
+    /// Brotli compression mode: generic, text or font
+    #[arg(long, default_value = "font")]
+    mode: BrotliMode,
+
     /// Number of threads for Brotli compression: 1=single-threaded (deterministic), 0=all cores,
     /// N=N threads.
     ///
@@ -32,13 +114,320 @@ struct Args {
     /// stream that any spec-compliant WOFF2 decoder accepts.
     #[arg(short, long, default_value = "1")]
     threads: usize,
+
+    /// Path to an XML file to embed as the WOFF2 extended metadata block
+    #[arg(long, value_name = "FILE")]
+    metadata: Option<PathBuf>,
+
+    /// Path to a file to embed as-is as the WOFF2 private data block
+    #[arg(long, value_name = "FILE")]
+    private_data: Option<PathBuf>,
+
+    /// Fail if a table checksum or head.checkSumAdjustment in the input does not match its data
+    #[arg(long)]
+    strict: bool,
+
+    /// Subset the font to these Unicode ranges, in CSS unicode-range syntax (e.g.
+    /// U+0020-007E,U+30??)
+    #[arg(long, value_name = "RANGES", value_delimiter = ',', value_parser = parse_unicode_range)]
+    unicodes: Vec<RangeInclusive<u32>>,
+
+    /// Subset the font to the characters of this text
+    #[arg(long)]
+    text: Option<String>,
+
+    /// Subset the font to these glyph IDs or ID ranges (e.g. 0-10,42), in addition to --unicodes
+    /// and --text
+    #[arg(long, value_name = "IDS", value_delimiter = ',', value_parser = parse_glyph_id_range)]
+    glyph_ids: Vec<RangeInclusive<u16>>,
+
+    /// Remove TrueType hinting: glyph instructions and the fpgm, prep, cvt, cvar, hdmx, LTSH and
+    /// VDMX tables
+    #[arg(long)]
+    strip_hinting: bool,
+
+    /// Leave out these tables (e.g. DSIG,LTSH)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    drop_tables: Vec<Tag>,
+
+    /// Encode only these tables (e.g. cmap,glyf,head,hhea,hmtx,loca,maxp,name,OS/2,post)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',', conflicts_with = "drop_tables")]
+    keep_tables: Vec<Tag>,
+
+    /// Split each font into unicode-range shards of at most N characters, and write a CSS file
+    /// with matching @font-face rules
+    #[arg(long, value_name = "N", conflicts_with_all = ["output", "unicodes", "text", "glyph_ids"])]
+    shard: Option<NonZeroUsize>,
+
+    /// Order in which --shard fills the shards with the font's characters
+    #[arg(long, value_name = "ORDER", value_enum, default_value_t = ShardOrder::Frequency, requires = "shard")]
+    shard_order: ShardOrder,
+
+    /// Split each font into the unicode-range shards listed in FILE, one shard per line as a
+    /// comma-separated list of ranges (e.g. U+0000-00FF,U+0131), and write a CSS file with
+    /// matching @font-face rules
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["shard", "output", "unicodes", "text", "glyph_ids"]
+    )]
+    shard_plan: Option<PathBuf>,
+
+    /// font-family name used in the CSS written with --shard or --shard-plan (defaults to the
+    /// input file name without extension)
+    #[arg(long, value_name = "NAME")]
+    css_family: Option<String>,
+
+    /// Show where the output bytes go: the size of each table and transformed glyf stream, and
+    /// an estimate of each table's share of the compressed data (compresses every table a second
+    /// time on its own)
+    #[arg(long)]
+    report: bool,
+
+    /// Encode at quality 11 with the glyf/loca transform on and off, 4 and 16 MiB Brotli windows
+    /// and automatic and 16 MiB blocks, in parallel, and keep the smallest output
+    #[arg(long, conflicts_with_all = ["quality", "lgwin", "lgblock", "threads", "report"])]
+    optimize: bool,
+
+    /// Write zlib-compressed WOFF 1.0 files (.woff) instead of WOFF2, for user agents without
+    /// WOFF2 support; the Brotli and transform settings do not apply
+    #[arg(long, conflicts_with_all = ["optimize", "report", "shard", "shard_plan"])]
+    woff: bool,
+
+    /// Keep encoded files in DIR, keyed by a hash of the input, the encode settings and the
+    /// ttf2woff2 version, and reuse them instead of compressing unchanged fonts again
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["optimize", "report", "woff"])]
+    cache_dir: Option<PathBuf>,
+
+    /// Size cap of --cache-dir in MiB; the least recently used files are removed beyond it
+    #[arg(long, value_name = "MIB", default_value = "256", requires = "cache_dir")]
+    cache_size: u64,
+
+    /// Output format of the conversion summary, not of the fonts; use --woff to write WOFF 1.0
+    /// files instead of WOFF2
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
 }
 
 fn main() -> ExitCode {
     let args = Args::parse();
+    match args.command {
+        Some(Command::Info(args)) => info(args),
+        None => encode_file(args.encode),
+    }
+}
 
-    let output = args.output.unwrap_or_else(|| args.input.with_extension("woff2"));
-    let quality = BrotliQuality::from(args.quality);
+/// A font to convert, found through one of the inputs.
+struct Job {
+    input: PathBuf,
+    /// Path of the font relative to the directory or glob base it was found in, used to mirror
+    /// the input tree under `--out-dir`
+    relative: PathBuf,
+    output: PathBuf,
+    /// Code points of the shard this job encodes, with `--shard` or `--shard-plan`
+    unicodes: Option<Vec<RangeInclusive<u32>>>,
+}
+
+impl Job {
+    /// The input path, followed by the shard's file name for shard jobs.
+    fn label(&self) -> String {
+        match (&self.unicodes, self.output.file_name()) {
+            (Some(_), Some(name)) => {
+                format!("{} ({})", self.input.display(), name.to_string_lossy())
+            }
+            _ => self.input.display().to_string(),
+        }
+    }
+}
+
+/// A CSS file with the `@font-face` rules of one font's shards.
+struct Stylesheet {
+    path: PathBuf,
+    family: String,
+    /// Indices of the shard jobs in [`Batch::jobs`]
+    jobs: Range<usize>,
+}
+
+/// The fonts to convert and the settings to convert them with.
+struct Batch {
+    jobs: Vec<Job>,
+    stylesheets: Vec<Stylesheet>,
+    options: EncodeOptions,
+    /// Settings to search with `--optimize`, replacing the quality and transform in `options`
+    optimize: Option<OptimizeOptions>,
+    /// Whether to produce a size breakdown of each output, with `--report`
+    report: bool,
+    /// Whether to write WOFF 1.0 instead of WOFF2, with `--woff`
+    woff: bool,
+    /// Where to reuse and store encoded files, with `--cache-dir`
+    cache: Option<EncodeCache>,
+    workers: usize,
+}
+
+/// A table of a converted font, as listed in `--format json` output.
+struct TableSizes {
+    tag: Tag,
+    orig_length: u32,
+    stored_length: u32,
+    transformed: bool,
+    /// The table's share of the compressed font data, in bytes: exact for WOFF 1.0, where each
+    /// table is compressed on its own, and the `--report` estimate for WOFF2.
+    compressed_share: u32,
+}
+
+/// Sizes of a successfully converted font.
+struct Converted {
+    input_size: usize,
+    output_size: usize,
+    elapsed: Duration,
+    /// The sizes of each table of the output, for `--format json`
+    tables: Option<Vec<TableSizes>>,
+    /// Size breakdown of the output, with `--report`
+    report: Option<EncodeReport>,
+    /// The winning settings and the output size of every candidate, with `--optimize`
+    optimized: Option<(Candidate, Vec<(Candidate, usize)>)>,
+}
+
+/// The step at which converting a font failed.
+#[derive(Clone, Copy)]
+enum Stage {
+    Read,
+    Encode,
+    Write,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Encode => "encode",
+            Self::Write => "write",
+        }
+    }
+}
+
+struct ConvertError {
+    stage: Stage,
+    path: PathBuf,
+    message: String,
+}
+
+impl ConvertError {
+    fn new(stage: Stage, path: &Path, error: impl Display) -> Self {
+        Self {
+            stage,
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.stage {
+            Stage::Read => "reading",
+            Stage::Encode => "encoding",
+            Stage::Write => "writing",
+        };
+        write!(f, "Error {action} {}: {}", self.path.display(), self.message)
+    }
+}
+
+fn encode_file(args: EncodeArgs) -> ExitCode {
+    let started = Instant::now();
+    let format = args.format;
+    // Keep stdout free for the font data in pipe mode
+    let pipe = args.inputs.iter().any(|input| is_stdio(input))
+        || args.output.as_deref().is_some_and(is_stdio);
+    let report = |text: &str| {
+        if pipe {
+            eprintln!("{text}");
+        } else {
+            println!("{text}");
+        }
+    };
+
+    let batch = match plan(args) {
+        Ok(batch) => batch,
+        Err(e) => {
+            match format {
+                Format::Text => eprintln!("{e}"),
+                Format::Json => report(&format!("{:#}", json!({ "error": { "message": e } }))),
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let with_info = matches!(format, Format::Json);
+    let results = run_jobs(&batch.jobs, batch.workers, |job| convert(job, &batch, with_info));
+    let mut failed = results.iter().any(|(_, result)| result.is_err());
+
+    let mut stylesheets = Vec::new();
+    for stylesheet in &batch.stylesheets {
+        let shards = &results[stylesheet.jobs.clone()];
+        // A stylesheet pointing at missing shards would break the page, so none is written
+        if shards.iter().any(|(_, result)| result.is_err()) {
+            continue;
+        }
+        match write_stylesheet(stylesheet, shards) {
+            Ok(()) => stylesheets.push(&stylesheet.path),
+            Err(e) => {
+                eprintln!("Error writing {}: {e}", stylesheet.path.display());
+                failed = true;
+            }
+        }
+    }
+
+    match (format, &results[..]) {
+        (Format::Json, _) => {
+            let json = encode_json(&batch, &results, &stylesheets, started.elapsed());
+            report(&format!("{json:#}"));
+        }
+        (Format::Text, [(job, Ok(converted))]) => report(&format!(
+            "{} -> {} ({} -> {} bytes, {:.1}% compression)",
+            job.input.display(),
+            job.output.display(),
+            converted.input_size,
+            converted.output_size,
+            compression(converted.input_size, converted.output_size)
+        )),
+        (Format::Text, [(_, Err(e))]) => eprintln!("{e}"),
+        (Format::Text, _) => print_summary(&results),
+    }
+    if matches!(format, Format::Text) {
+        for (job, result) in &results {
+            if let Ok(Converted { optimized: Some((winner, sizes)), .. }) = result {
+                let best = format!("{} candidates, best: {winner}", sizes.len());
+                if results.len() > 1 {
+                    report(&format!("{}: {best}", job.label()));
+                } else {
+                    report(&format!("Optimized over {best}"));
+                }
+            }
+        }
+        for (job, result) in &results {
+            if let Ok(Converted { report: Some(size_report), .. }) = result {
+                if results.len() > 1 {
+                    report(&format!("\n{}:", job.label()));
+                }
+                report(&format_report(size_report));
+            }
+        }
+        for path in &stylesheets {
+            report(&format!("Wrote {}", path.display()));
+        }
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+/// Resolve the encode options and the list of fonts to convert from the command line.
+fn plan(args: EncodeArgs) -> Result<Batch, String> {
+    if args.cache_dir.is_some() && matches!(args.format, Format::Json) {
+        return Err("--cache-dir cannot be combined with --format json, whose per-table \
+                    compressed shares need the compression that the cache skips"
+            .to_string());
+    }
 
     let threads = match args.threads {
         0 => available_parallelism().ok(),
@@ -46,9 +435,678 @@ fn main() -> ExitCode {
         n => NonZeroUsize::new(n),
     };
 
-    let options = EncodeOptions { quality, threads, ..EncodeOptions::default() };
+    let metadata = match &args.metadata {
+        Some(path) => Some(
+            read_to_string(path).map_err(|e| format!("Error reading {}: {e}", path.display()))?,
+        ),
+        None => None,
+    };
+
+    let private_data = match &args.private_data {
+        Some(path) => {
+            Some(read(path).map_err(|e| format!("Error reading {}: {e}", path.display()))?)
+        }
+        None => None,
+    };
+
+    let subset = (!args.unicodes.is_empty() || args.text.is_some() || !args.glyph_ids.is_empty())
+        .then(|| {
+            let text = args.text.iter().flat_map(|text| text.chars());
+            let mut unicodes = args.unicodes;
+            unicodes.extend(text.map(|c| u32::from(c)..=u32::from(c)));
+            SubsetSpec { unicodes, glyph_ids: args.glyph_ids }
+        });
+
+    let table_filter = if !args.keep_tables.is_empty() {
+        Some(TableFilter::Keep(args.keep_tables))
+    } else if !args.drop_tables.is_empty() {
+        Some(TableFilter::Drop(args.drop_tables))
+    } else {
+        None
+    };
+
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(args.quality),
+        lgwin: args.lgwin,
+        lgblock: args.lgblock,
+        mode: args.mode,
+        threads,
+        metadata,
+        private_data,
+        verify_checksums: args.strict,
+        subset,
+        strip_hinting: args.strip_hinting,
+        table_filter,
+        ..EncodeOptions::default()
+    };
+
+    let extension = if args.woff { "woff" } else { "woff2" };
+    let mut jobs = collect_jobs(&args.inputs, extension)?;
+    if jobs.is_empty() {
+        return Err("No TTF, OTF, TTC or WOFF files found".to_string());
+    }
+    let stdin = jobs.iter().any(|job| is_stdio(&job.input));
+    if stdin && jobs.len() > 1 {
+        return Err("Reading from stdin ('-') cannot be combined with other inputs".to_string());
+    }
+    if let Some(output) = args.output {
+        if jobs.len() > 1 {
+            return Err("--output requires a single input file; use --out-dir for multiple inputs"
+                .to_string());
+        }
+        jobs[0].output = output;
+    } else if stdin {
+        if args.out_dir.is_some() {
+            return Err("--out-dir cannot be used when reading from stdin; use --output instead"
+                .to_string());
+        }
+        jobs[0].output = PathBuf::from("-");
+    } else if let Some(out_dir) = &args.out_dir {
+        for job in &mut jobs {
+            job.output = out_dir.join(&job.relative).with_extension(extension);
+        }
+    }
+
+    let shard_plan = match (args.shard, &args.shard_plan) {
+        (Some(codepoints_per_shard), _) => Some(match args.shard_order {
+            ShardOrder::Frequency => ShardPlan::Frequency { codepoints_per_shard },
+            ShardOrder::Codepoint => ShardPlan::Sequential { codepoints_per_shard },
+        }),
+        (None, Some(path)) => Some(read_shard_plan(path)?),
+        (None, None) => None,
+    };
+    let mut stylesheets = Vec::new();
+    if let Some(shard_plan) = shard_plan {
+        if stdin {
+            return Err(
+                "--shard and --shard-plan cannot be used when reading from stdin".to_string()
+            );
+        }
+        let mut shard_jobs = Vec::new();
+        for job in jobs {
+            let ttf_data = read(&job.input)
+                .map_err(|e| format!("Error reading {}: {e}", job.input.display()))?;
+            let slices = shard_plan
+                .slices(&ttf_data)
+                .map_err(|e| format!("Error sharding {}: {e}", job.input.display()))?;
+            let stem =
+                |path: &Path| path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let output_stem = stem(&job.output);
+            let start = shard_jobs.len();
+            for (index, unicodes) in slices.into_iter().enumerate() {
+                shard_jobs.push(Job {
+                    input: job.input.clone(),
+                    relative: job.relative.clone(),
+                    output: job.output.with_file_name(format!("{output_stem}.{index}.woff2")),
+                    unicodes: Some(unicodes),
+                });
+            }
+            stylesheets.push(Stylesheet {
+                path: job.output.with_extension("css"),
+                family: args.css_family.clone().unwrap_or_else(|| stem(&job.input)),
+                jobs: start..shard_jobs.len(),
+            });
+        }
+        jobs = shard_jobs;
+    }
+
+    if let Some(job) = jobs
+        .iter()
+        .find(|job| !is_stdio(&job.input) && job.output == job.input)
+    {
+        return Err(format!(
+            "Output {} would overwrite its input; use --output or --out-dir",
+            job.output.display()
+        ));
+    }
+    // Parallel workers writing the same file would silently lose all but one result
+    let mut outputs = HashMap::new();
+    let written = jobs
+        .iter()
+        .filter(|job| !is_stdio(&job.output))
+        .map(|job| (&job.output, &job.input))
+        .chain(stylesheets.iter().filter_map(|stylesheet| {
+            let job = jobs.get(stylesheet.jobs.start)?;
+            Some((&stylesheet.path, &job.input))
+        }));
+    for (output, input) in written {
+        if let Some(other) = outputs.insert(output, input) {
+            return Err(format!(
+                "{} and {} would both be written to {}",
+                other.display(),
+                input.display(),
+                output.display()
+            ));
+        }
+    }
+
+    let workers = match args.jobs {
+        0 => available_parallelism().map_or(1, NonZeroUsize::get),
+        n => n,
+    };
+
+    Ok(Batch {
+        jobs,
+        stylesheets,
+        options,
+        optimize: args.optimize.then(OptimizeOptions::default),
+        report: args.report,
+        woff: args.woff,
+        cache: args.cache_dir.map(|dir| {
+            EncodeCache::new(dir).with_max_bytes(args.cache_size.saturating_mul(1 << 20))
+        }),
+        workers,
+    })
+}
+
+/// Expand the inputs into the fonts to convert, each written next to its input with `extension`
+/// by default.
+///
+/// Directories are searched recursively for files with a font extension. Inputs that do not
+/// exist but contain glob metacharacters are expanded as patterns; any other input is taken as
+/// a file as-is, so a missing file is reported when it is converted.
+fn collect_jobs(inputs: &[PathBuf], extension: &str) -> Result<Vec<Job>, String> {
+    let mut seen = HashSet::new();
+    let mut jobs = Vec::new();
+    let mut push = |input: PathBuf, relative: PathBuf| {
+        if seen.insert(input.clone()) {
+            let output = input.with_extension(extension);
+            jobs.push(Job { input, relative, output, unicodes: None });
+        }
+    };
+
+    for input in inputs {
+        if input.is_dir() {
+            for entry in WalkDir::new(input).sort_by_file_name() {
+                let entry = entry.map_err(|e| format!("Error reading {}: {e}", input.display()))?;
+                if entry.file_type().is_file() && is_font(entry.path()) {
+                    let relative = entry.path().strip_prefix(input).unwrap_or(entry.path());
+                    push(entry.path().to_path_buf(), relative.to_path_buf());
+                }
+            }
+        } else if !input.exists() && is_pattern(input) {
+            let pattern = input.to_string_lossy();
+            let paths = glob(&pattern).map_err(|e| format!("Invalid pattern {pattern}: {e}"))?;
+            let base = glob_base(input);
+            let mut matched = false;
+            for path in paths {
+                let path =
+                    path.map_err(|e| format!("Error reading {}: {e}", e.path().display()))?;
+                if path.is_file() {
+                    matched = true;
+                    let relative = path.strip_prefix(&base).unwrap_or(&path).to_path_buf();
+                    push(path, relative);
+                }
+            }
+            if !matched {
+                return Err(format!("No files match {pattern}"));
+            }
+        } else {
+            let relative = input.file_name().map_or_else(|| input.clone(), PathBuf::from);
+            push(input.clone(), relative);
+        }
+    }
+
+    Ok(jobs)
+}
+
+/// Read a shard plan: one shard per non-empty line, as comma-separated `unicode-range` values.
+/// Lines starting with `#` are comments.
+fn read_shard_plan(path: &Path) -> Result<ShardPlan, String> {
+    let plan =
+        read_to_string(path).map_err(|e| format!("Error reading {}: {e}", path.display()))?;
+    let slices = plan
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(parse_unicode_range)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Error reading {}: {e}", path.display()))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ShardPlan::Explicit(slices))
+}
+
+/// Write the `@font-face` rules of a font's shards, each pointing at its WOFF2 file by name, since
+/// the stylesheet is written next to the shards.
+fn write_stylesheet(
+    stylesheet: &Stylesheet,
+    shards: &[(&Job, Result<Converted, ConvertError>)],
+) -> io::Result<()> {
+    let rules: Vec<_> = shards
+        .iter()
+        .map(|(job, _)| {
+            let url = job.output.file_name().unwrap_or_default().to_string_lossy();
+            font_face_css(&stylesheet.family, &url, job.unicodes.as_deref().unwrap_or_default())
+        })
+        .collect();
+    write(&stylesheet.path, rules.join("\n"))
+}
+
+/// Parse a CSS `unicode-range` value: `U+41`, `U+41-5A` or `U+4??`.
+fn parse_unicode_range(value: &str) -> Result<RangeInclusive<u32>, String> {
+    let value = value.trim();
+    let digits = value
+        .strip_prefix("U+")
+        .or_else(|| value.strip_prefix("u+"))
+        .ok_or_else(|| format!("expected U+XXXX, found {value}"))?;
+    let parse = |digits: &str| {
+        u32::from_str_radix(digits, 16).map_err(|e| format!("invalid code point {digits}: {e}"))
+    };
+    let range = match digits.split_once('-') {
+        Some((start, end)) => parse(start)?..=parse(end)?,
+        None if digits.contains('?') => {
+            parse(&digits.replace('?', "0"))?..=parse(&digits.replace('?', "F"))?
+        }
+        None => parse(digits)?..=parse(digits)?,
+    };
+    if range.is_empty() || *range.end() > 0x10FFFF {
+        return Err(format!("invalid range {value}"));
+    }
+    Ok(range)
+}
+
+/// Parse a glyph ID (`42`) or an inclusive range of glyph IDs (`0-10`).
+fn parse_glyph_id_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let parse = |id: &str| {
+        id.trim()
+            .parse::<u16>()
+            .map_err(|e| format!("invalid glyph ID {id}: {e}"))
+    };
+    let range = match value.split_once('-') {
+        Some((start, end)) => parse(start)?..=parse(end)?,
+        None => parse(value)?..=parse(value)?,
+    };
+    if range.is_empty() {
+        return Err(format!("invalid range {value}"));
+    }
+    Ok(range)
+}
+
+/// Whether the path is `-`, standing for stdin as an input and stdout as an output.
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn is_font(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            FONT_EXTENSIONS
+                .iter()
+                .any(|font| extension.eq_ignore_ascii_case(font))
+        })
+}
+
+fn is_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// The leading components of a glob pattern that contain no metacharacters.
+fn glob_base(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|component| !is_pattern(component.as_ref()))
+        .collect()
+}
+
+/// Convert the jobs on a pool of `workers` threads, returning the results in input order.
+fn run_jobs<T: Send>(
+    jobs: &[Job],
+    workers: usize,
+    convert: impl Fn(&Job) -> T + Sync,
+) -> Vec<(&Job, T)> {
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.clamp(1, jobs.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            break results;
+                        };
+                        results.push((index, convert(job)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results
+        .into_iter()
+        .map(|(index, result)| (&jobs[index], result))
+        .collect()
+}
+
+fn convert(job: &Job, batch: &Batch, with_info: bool) -> Result<Converted, ConvertError> {
+    let started = Instant::now();
+    let ttf_data = if is_stdio(&job.input) {
+        let mut data = Vec::new();
+        io::stdin().lock().read_to_end(&mut data).map(|_| data)
+    } else {
+        read(&job.input)
+    }
+    .map_err(|e| ConvertError::new(Stage::Read, &job.input, e))?;
+
+    let mut options = batch.options.clone();
+    if let Some(unicodes) = &job.unicodes {
+        options.subset = Some(SubsetSpec {
+            unicodes: unicodes.clone(),
+            ..SubsetSpec::default()
+        });
+    }
+    // The per-table compressed shares of `--format json` come from the same per-table Brotli
+    // passes as `--report`
+    let (woff2_data, report, optimized) = if let Some(optimize) = &batch.optimize {
+        encode_optimized(&ttf_data, options.clone(), optimize).and_then(|optimized| {
+            let report = with_info
+                .then(|| {
+                    let Candidate { transform_glyf_loca, quality, lgwin, lgblock } =
+                        optimized.winner;
+                    let options = EncodeOptions {
+                        quality,
+                        lgwin,
+                        lgblock,
+                        transform_glyf_loca,
+                        threads: None,
+                        ..options
+                    };
+                    encode_with_report(&ttf_data, options).map(|(_, report)| report)
+                })
+                .transpose()?;
+            Ok((optimized.data, report, Some((optimized.winner, optimized.sizes))))
+        })
+    } else if batch.woff {
+        encode_woff1(&ttf_data, options).map(|data| (data, None, None))
+    } else if batch.report || with_info {
+        encode_with_report(&ttf_data, options).map(|(data, report)| (data, Some(report), None))
+    } else if let Some(cache) = &batch.cache {
+        cache.encode(&ttf_data, options).map(|data| (data, None, None))
+    } else {
+        encode_with_options(&ttf_data, options).map(|data| (data, None, None))
+    }
+    .map_err(|e| ConvertError::new(Stage::Encode, &job.input, e))?;
+
+    let written = if is_stdio(&job.output) {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&woff2_data).and_then(|()| stdout.flush())
+    } else {
+        job.output
+            .parent()
+            .map_or(Ok(()), create_dir_all)
+            .and_then(|()| write(&job.output, &woff2_data))
+    };
+    written.map_err(|e| ConvertError::new(Stage::Write, &job.output, e))?;
+
+    Ok(Converted {
+        input_size: ttf_data.len(),
+        output_size: woff2_data.len(),
+        elapsed: started.elapsed(),
+        tables: with_info.then(|| match &report {
+            Some(report) => report.tables.iter().map(TableSizes::from).collect(),
+            None => woff1_tables(&woff2_data),
+        }),
+        report: report.filter(|_| batch.report),
+        optimized,
+    })
+}
+
+impl From<&TableReport> for TableSizes {
+    fn from(table: &TableReport) -> Self {
+        Self {
+            tag: table.tag,
+            orig_length: table.orig_length,
+            stored_length: table.stored_length(),
+            transformed: table.transform_length.is_some(),
+            compressed_share: table.compressed_share,
+        }
+    }
+}
+
+/// The table directory of a WOFF 1.0 file written by `encode_woff1`. WOFF 1.0 has no
+/// transformations, so every table is stored at its original length, and zlib-compressed on
+/// its own.
+fn woff1_tables(woff_data: &[u8]) -> Vec<TableSizes> {
+    let num_tables = usize::from(u16::from_be_bytes([woff_data[12], woff_data[13]]));
+    let be_u32 = |bytes: &[u8]| u32::from_be_bytes(bytes.try_into().unwrap());
+    woff_data[44..44 + 20 * num_tables]
+        .chunks_exact(20)
+        .map(|entry| TableSizes {
+            tag: Tag::from(<[u8; 4]>::try_from(&entry[..4]).unwrap()),
+            orig_length: be_u32(&entry[12..16]),
+            stored_length: be_u32(&entry[12..16]),
+            transformed: false,
+            compressed_share: be_u32(&entry[8..12]),
+        })
+        .collect()
+}
+
+/// Percentage of the input size saved by the conversion.
+fn compression(input_size: usize, output_size: usize) -> f64 {
+    (1.0 - output_size as f64 / input_size as f64) * 100.0
+}
+
+/// Print a size table of all converted files, followed by the errors of the failed ones.
+fn print_summary(results: &[(&Job, Result<Converted, ConvertError>)]) {
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    let total_label = format!("Total ({} converted, {failed} failed)", results.len() - failed);
+    let width = results
+        .iter()
+        .map(|(job, _)| job.label().len())
+        .chain([total_label.len()])
+        .max()
+        .unwrap_or(0);
+
+    println!("{:<width$}  {:>10}  {:>10}  {:>11}", "Input", "Original", "WOFF2", "Compression");
+    let (mut input_total, mut output_total) = (0, 0);
+    for (job, result) in results {
+        let input = job.label();
+        match result {
+            Ok(converted) => {
+                input_total += converted.input_size;
+                output_total += converted.output_size;
+                println!(
+                    "{input:<width$}  {:>10}  {:>10}  {:>10.1}%",
+                    converted.input_size,
+                    converted.output_size,
+                    compression(converted.input_size, converted.output_size)
+                );
+            }
+            Err(_) => println!("{input:<width$}  {:>10}  {:>10}  {:>11}", "-", "-", "failed"),
+        }
+    }
+    if failed < results.len() {
+        println!(
+            "{total_label:<width$}  {input_total:>10}  {output_total:>10}  {:>10.1}%",
+            compression(input_total, output_total)
+        );
+    } else {
+        println!("{total_label}");
+    }
+
+    for (_, result) in results {
+        if let Err(e) = result {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// A table of the sizes in a `--report`, with the transformed glyf streams under their table.
+fn format_report(report: &EncodeReport) -> String {
+    let share = |size: u32| 100.0 * f64::from(size) / f64::from(report.length.max(1));
+    let mut lines = vec![format!(
+        "{:<24}  {:>10}  {:>10}  {:>10}  {:>6}",
+        "Table", "Original", "Stored", "Compressed", "Share"
+    )];
+    for table in &report.tables {
+        lines.push(format!(
+            "{:<24}  {:>10}  {:>10}  {:>10}  {:>5.1}%",
+            table.tag.to_string(),
+            table.orig_length,
+            table.stored_length(),
+            table.compressed_share,
+            share(table.compressed_share)
+        ));
+        for (name, size) in table.glyf_streams.iter().flat_map(GlyfStreams::named) {
+            lines.push(format!("  {name:<22}  {:>10}  {size:>10}", ""));
+        }
+    }
+    for (name, size) in [
+        ("Header and directory", report.directory_length),
+        ("Metadata", report.metadata_length),
+        ("Private data", report.private_data_length),
+    ] {
+        if size > 0 {
+            lines.push(format!(
+                "{name:<24}  {:>10}  {:>10}  {size:>10}  {:>5.1}%",
+                "",
+                "",
+                share(size)
+            ));
+        }
+    }
+    lines.push(format!(
+        "{:<24}  {:>10}  {:>10}  {:>10}  {:>5.1}%",
+        "Total", report.total_sfnt_size, "", report.length, 100.0
+    ));
+    lines.push(
+        "Compressed table sizes are estimates from compressing each table on its own".to_string(),
+    );
+    lines.join("\n")
+}
 
-    let ttf_data = match read(&args.input) {
+/// The settings of an `--optimize` candidate in `--format json` output.
+fn candidate_json(candidate: &Candidate) -> serde_json::Value {
+    json!({
+        "transform_glyf_loca": candidate.transform_glyf_loca,
+        "quality": u8::from(candidate.quality),
+        "lgwin": u8::from(candidate.lgwin),
+        "lgblock": u8::from(candidate.lgblock),
+    })
+}
+
+/// The `report` object of a file in `--format json` output.
+fn report_json(report: &EncodeReport) -> serde_json::Value {
+    let tables: Vec<_> = report
+        .tables
+        .iter()
+        .map(|table| {
+            let streams = table.glyf_streams.map(|streams| {
+                let streams: serde_json::Map<_, _> = streams
+                    .named()
+                    .into_iter()
+                    .map(|(name, size)| (name.to_string(), json!(size)))
+                    .collect();
+                streams
+            });
+            json!({
+                "tag": table.tag.to_string(),
+                "orig_length": table.orig_length,
+                "transform_length": table.transform_length,
+                "brotli_length": table.brotli_length,
+                "compressed_share": table.compressed_share,
+                "glyf_streams": streams,
+            })
+        })
+        .collect();
+    json!({
+        "length": report.length,
+        "total_sfnt_size": report.total_sfnt_size,
+        "directory_length": report.directory_length,
+        "total_compressed_size": report.total_compressed_size,
+        "metadata_length": report.metadata_length,
+        "private_data_length": report.private_data_length,
+        "tables": tables,
+    })
+}
+
+/// The result of an encode run for `--format json`.
+fn encode_json(
+    batch: &Batch,
+    results: &[(&Job, Result<Converted, ConvertError>)],
+    stylesheets: &[&PathBuf],
+    elapsed: Duration,
+) -> serde_json::Value {
+    let files: Vec<_> = results
+        .iter()
+        .map(|(job, result)| match result {
+            Ok(converted) => {
+                let tables = converted.tables.as_ref().map(|tables| {
+                    let tables: Vec<_> = tables
+                        .iter()
+                        .map(|table| {
+                            json!({
+                                "tag": table.tag.to_string(),
+                                "orig_length": table.orig_length,
+                                "stored_length": table.stored_length,
+                                "transformed": table.transformed,
+                                "compressed_share": table.compressed_share,
+                            })
+                        })
+                        .collect();
+                    tables
+                });
+                json!({
+                    "input": job.input,
+                    "output": job.output,
+                    "input_size": converted.input_size,
+                    "output_size": converted.output_size,
+                    "ratio": converted.output_size as f64 / converted.input_size as f64,
+                    "compression_percent": compression(converted.input_size, converted.output_size),
+                    "elapsed_seconds": converted.elapsed.as_secs_f64(),
+                    "tables": tables,
+                    "report": converted.report.as_ref().map(report_json),
+                    "optimized": converted.optimized.as_ref().map(|(winner, sizes)| {
+                        let candidates: Vec<_> = sizes
+                            .iter()
+                            .map(|(candidate, size)| {
+                                json!({ "settings": candidate_json(candidate), "size": size })
+                            })
+                            .collect();
+                        json!({ "winner": candidate_json(winner), "candidates": candidates })
+                    }),
+                    "error": null,
+                })
+            }
+            Err(e) => json!({
+                "input": job.input,
+                "output": job.output,
+                "error": {
+                    "stage": e.stage.name(),
+                    "path": e.path,
+                    "message": e.message,
+                },
+            }),
+        })
+        .collect();
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+
+    json!({
+        "quality": u8::from(batch.options.quality),
+        "lgwin": u8::from(batch.options.lgwin),
+        "lgblock": u8::from(batch.options.lgblock),
+        "mode": batch.options.mode.to_string(),
+        "threads": batch.options.threads.map_or(1, NonZeroUsize::get),
+        "jobs": batch.workers.clamp(1, batch.jobs.len()),
+        "elapsed_seconds": elapsed.as_secs_f64(),
+        "converted": results.len() - failed,
+        "failed": failed,
+        "files": files,
+        "stylesheets": stylesheets,
+    })
+}
+
+fn info(args: InfoArgs) -> ExitCode {
+    let woff2_data = match read(&args.input) {
         Ok(data) => data,
         Err(e) => {
             eprintln!("Error reading {}: {e}", args.input.display());
@@ -56,27 +1114,258 @@ fn main() -> ExitCode {
         }
     };
 
-    let woff2_data = match encode_with_options(&ttf_data, options) {
-        Ok(data) => data,
+    let info = match Woff2Info::try_from(&woff2_data[..]) {
+        Ok(info) => info,
         Err(e) => {
-            eprintln!("Error encoding: {e}");
+            eprintln!("Error reading {}: {e}", args.input.display());
             return ExitCode::FAILURE;
         }
     };
 
-    if let Err(e) = write(&output, &woff2_data) {
-        eprintln!("Error writing {}: {e}", output.display());
-        return ExitCode::FAILURE;
+    match args.format {
+        Format::Text => print_info(&info),
+        Format::Json => println!("{:#}", info_json(&info)),
     }
 
-    let compression = (1.0 - woff2_data.len() as f64 / ttf_data.len() as f64) * 100.0;
-    println!(
-        "{} -> {} ({} -> {} bytes, {compression:.1}% compression)",
-        args.input.display(),
-        output.display(),
-        ttf_data.len(),
-        woff2_data.len()
-    );
-
     ExitCode::SUCCESS
 }
+
+fn flavor(flavor: u32) -> String {
+    Tag::from(flavor.to_be_bytes()).to_string()
+}
+
+fn print_info(info: &Woff2Info) {
+    let (major, minor) = info.version;
+    println!("Flavor:                {}", flavor(info.flavor));
+    println!("Length:                {}", info.length);
+    println!("Total SFNT size:       {}", info.total_sfnt_size);
+    println!("Total compressed size: {}", info.total_compressed_size);
+    println!("Version:               {major}.{minor}");
+    match &info.metadata {
+        Some(_) => println!(
+            "Metadata:              {} bytes ({} compressed) at offset {}",
+            info.meta_orig_length, info.meta_length, info.meta_offset
+        ),
+        None => println!("Metadata:              none"),
+    }
+    match &info.private_data {
+        Some(_) => {
+            println!(
+                "Private data:          {} bytes at offset {}",
+                info.priv_length, info.priv_offset
+            )
+        }
+        None => println!("Private data:          none"),
+    }
+
+    println!();
+    println!("Index  Tag   Transform  Orig length  Transform length");
+    for (index, table) in info.tables.iter().enumerate() {
+        let transform_length = table.transform_length.map_or("-".to_string(), |l| l.to_string());
+        println!(
+            "{index:>5}  {}  {:>9}  {:>11}  {transform_length:>16}",
+            table.tag, table.transform_version, table.orig_length
+        );
+    }
+
+    if let Some(collection) = &info.collection {
+        println!();
+        println!("Collection version {:#010x}", collection.version);
+        for (index, font) in collection.fonts.iter().enumerate() {
+            let tables: Vec<_> = font.table_indices.iter().map(usize::to_string).collect();
+            println!("Font {index} ({}): tables {}", flavor(font.flavor), tables.join(" "));
+        }
+    }
+}
+
+fn info_json(info: &Woff2Info) -> serde_json::Value {
+    let tables: Vec<_> = info
+        .tables
+        .iter()
+        .map(|table| {
+            json!({
+                "tag": table.tag.to_string(),
+                "transform_version": table.transform_version,
+                "orig_length": table.orig_length,
+                "transform_length": table.transform_length,
+            })
+        })
+        .collect();
+    let collection = info.collection.as_ref().map(|collection| {
+        let fonts: Vec<_> = collection
+            .fonts
+            .iter()
+            .map(|font| json!({ "flavor": flavor(font.flavor), "tables": font.table_indices }))
+            .collect();
+        json!({ "version": collection.version, "fonts": fonts })
+    });
+    let private_data = info
+        .private_data
+        .as_ref()
+        .map(|data| data.iter().map(|b| format!("{b:02x}")).collect::<String>());
+
+    json!({
+        "flavor": flavor(info.flavor),
+        "length": info.length,
+        "total_sfnt_size": info.total_sfnt_size,
+        "total_compressed_size": info.total_compressed_size,
+        "major_version": info.version.0,
+        "minor_version": info.version.1,
+        "meta_offset": info.meta_offset,
+        "meta_length": info.meta_length,
+        "meta_orig_length": info.meta_orig_length,
+        "priv_offset": info.priv_offset,
+        "priv_length": info.priv_length,
+        "tables": tables,
+        "collection": collection,
+        "metadata": info.metadata,
+        "private_data": private_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    const FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/WarpnineSans-Regular.ttf");
+
+    /// A directory unique to the test holding copies of the fixture font at `fonts`, plus a file
+    /// that is not a font.
+    fn font_tree(name: &str, fonts: &[&str]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ttf2woff2-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for font in fonts {
+            let path = dir.join(font);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            fs::copy(FIXTURE, path).unwrap();
+        }
+        write(dir.join("README.txt"), "not a font").unwrap();
+        dir
+    }
+
+    fn plan_args(args: &[&str]) -> Result<Batch, String> {
+        let args = Args::try_parse_from(["ttf2woff2"].iter().chain(args)).unwrap();
+        plan(args.encode)
+    }
+
+    fn outputs(batch: &Batch) -> Vec<PathBuf> {
+        batch.jobs.iter().map(|job| job.output.clone()).collect()
+    }
+
+    #[test]
+    fn test_parse_unicode_range() {
+        assert_eq!(parse_unicode_range("U+41"), Ok(0x41..=0x41));
+        assert_eq!(parse_unicode_range(" u+41-5a "), Ok(0x41..=0x5A));
+        assert_eq!(parse_unicode_range("U+4??"), Ok(0x400..=0x4FF));
+        assert_eq!(parse_unicode_range("U+10FFFF"), Ok(0x10FFFF..=0x10FFFF));
+        for invalid in ["41", "U+", "U+5A-41", "U+110000", "U+0-110000", "U+XY", "U+4?-50"] {
+            assert!(parse_unicode_range(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_glob_base() {
+        assert_eq!(glob_base(Path::new("fonts/*/x.ttf")), Path::new("fonts"));
+        assert_eq!(glob_base(Path::new("fonts/latin/*.ttf")), Path::new("fonts/latin"));
+        assert_eq!(glob_base(Path::new("fonts/[ab]/x.ttf")), Path::new("fonts"));
+        assert_eq!(glob_base(Path::new("*.ttf")), Path::new(""));
+    }
+
+    #[test]
+    fn test_collect_jobs() {
+        let dir = font_tree("collect-jobs", &["a/x.ttf", "a/sub/y.otf", "b/x.woff"]);
+
+        // Directories are searched recursively, in file name order, for fonts only
+        let jobs = collect_jobs(&[dir.join("a")], "woff2").unwrap();
+        let found: Vec<_> = jobs.iter().map(|job| (&job.relative, &job.output)).collect();
+        let expected = [
+            (PathBuf::from("sub/y.otf"), dir.join("a/sub/y.woff2")),
+            (PathBuf::from("x.ttf"), dir.join("a/x.woff2")),
+        ];
+        assert_eq!(found, expected.iter().map(|(r, o)| (r, o)).collect::<Vec<_>>());
+
+        // Inputs found twice are converted once
+        let jobs = collect_jobs(&[dir.join("a"), dir.join("a/x.ttf")], "woff").unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|job| job.output.extension().unwrap() == "woff"));
+
+        // Glob matches are relative to the pattern's base
+        let jobs = collect_jobs(&[dir.join("*/x.*")], "woff2").unwrap();
+        let relative: Vec<_> = jobs.iter().map(|job| job.relative.clone()).collect();
+        assert_eq!(relative, [PathBuf::from("a/x.ttf"), PathBuf::from("b/x.woff")]);
+        assert!(collect_jobs(&[dir.join("*/*.ttc")], "woff2").is_err());
+
+        // Files are taken as-is, even when missing
+        let jobs = collect_jobs(&[dir.join("missing.ttf")], "woff2").unwrap();
+        assert_eq!(jobs[0].relative, Path::new("missing.ttf"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_out_dir_mirrors_inputs() {
+        let dir = font_tree("plan-out-dir", &["a/x.ttf", "a/sub/y.otf"]);
+        let out = dir.join("out");
+
+        let batch =
+            plan_args(&[dir.join("a").to_str().unwrap(), "--out-dir", out.to_str().unwrap()])
+                .unwrap();
+        assert_eq!(outputs(&batch), [out.join("sub/y.woff2"), out.join("x.woff2")]);
+
+        let pattern = dir.join("*/x.ttf");
+        let batch =
+            plan_args(&[pattern.to_str().unwrap(), "--out-dir", out.to_str().unwrap(), "--woff"])
+                .unwrap();
+        assert_eq!(outputs(&batch), [out.join("a/x.woff")]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_rejects_output_collisions() {
+        let dir = font_tree("plan-collisions", &["a/x.ttf", "b/x.ttf", "a/y.ttf", "a/y.otf"]);
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let out = path("out");
+
+        // Flattened into the same --out-dir
+        let error = plan_args(&[&path("a/x.ttf"), &path("b/x.ttf"), "--out-dir", &out]).err();
+        assert!(
+            error
+                .unwrap()
+                .contains(&format!("would both be written to {out}/x.woff2"))
+        );
+        // The same file name mirrored from different directories does not collide
+        assert!(plan_args(&[&path("*/x.ttf"), "--out-dir", &out]).is_ok());
+        // Two fonts with the same stem next to each other, and their shards
+        assert!(plan_args(&[&path("a/y.ttf"), &path("a/y.otf")]).is_err());
+        assert!(plan_args(&[&path("a/y.ttf"), &path("a/y.otf"), "--shard", "5000"]).is_err());
+        // An output overwriting its input
+        assert!(plan_args(&[&path("a/x.ttf"), "-o", &path("a/x.ttf")]).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_options_and_workers() {
+        let batch = plan_args(&[FIXTURE, "-q", "5", "-j", "3", "-t", "4", "--strict"]).unwrap();
+        assert_eq!(batch.workers, 3);
+        assert_eq!(u8::from(batch.options.quality), 5);
+        assert_eq!(batch.options.threads, NonZeroUsize::new(4));
+        assert!(batch.options.verify_checksums);
+        assert_eq!(outputs(&batch), [Path::new(FIXTURE).with_extension("woff2")]);
+
+        let batch = plan_args(&[FIXTURE, "-j", "0", "-t", "1", "--text", "ab"]).unwrap();
+        assert_eq!(batch.workers, available_parallelism().map_or(1, NonZeroUsize::get));
+        assert_eq!(batch.options.threads, None);
+        assert_eq!(batch.options.subset.unwrap().unicodes, [0x61..=0x61, 0x62..=0x62]);
+
+        assert!(plan_args(&[FIXTURE, FIXTURE, "-o", "x.woff2"]).is_ok());
+        assert!(plan_args(&[FIXTURE, "-", "-o", "x.woff2"]).is_err());
+        let batch = plan_args(&["-"]).unwrap();
+        assert_eq!(outputs(&batch), [PathBuf::from("-")]);
+        assert!(plan_args(&["-", "--out-dir", "out"]).is_err());
+    }
+}
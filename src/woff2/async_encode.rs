@@ -0,0 +1,74 @@
+use std::{
+    panic,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use super::encoder::{EncodeOptions, Encoder};
+use crate::Error;
+
+/// Sets the flag when dropped, telling the blocking encode that nobody awaits it anymore.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Encode a TTF or OTF font as WOFF2 on Tokio's blocking thread pool.
+///
+/// Encoding at high qualities keeps a CPU busy for seconds on large fonts, which would stall the
+/// runtime if done on an async worker thread. This runs [`encode_with_options`] through
+/// [`tokio::task::spawn_blocking`] instead, so it must be called from within a Tokio runtime.
+///
+/// Dropping the returned future cancels the encode: the blocking task stops at the next point
+/// [`EncodeOptions::cancel`] would be polled, freeing its thread. The progress and cancel hooks
+/// of `options` apply as usual.
+///
+/// Requires the `async` feature.
+///
+/// See [`encode`](super::encoder::encode) for argument and error semantics. A panic inside the
+/// encoder is resumed on the awaiting task.
+///
+/// [`encode_with_options`]: super::encoder::encode_with_options
+pub async fn encode_async<T>(ttf_data: T, options: EncodeOptions) -> Result<Vec<u8>, Error>
+where
+    T: AsRef<[u8]> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(cancelled.clone());
+    let task = tokio::task::spawn_blocking(move || {
+        Encoder::new(ttf_data.as_ref(), options)?
+            .with_cancel_flag(cancelled)
+            .try_into()
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+        // Only when the runtime shuts down before the task has run
+        Err(_) => Err(Error::Cancelled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read, path::PathBuf};
+
+    use super::*;
+
+    #[test]
+    fn test_cancelled_encode_stops() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/WarpnineSans-Regular.ttf");
+        let ttf_data = read(path).unwrap();
+        let cancelled = Arc::new(AtomicBool::new(true));
+        let result: Result<Vec<u8>, Error> = Encoder::new(&ttf_data, EncodeOptions::default())
+            .unwrap()
+            .with_cancel_flag(cancelled)
+            .try_into();
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+}
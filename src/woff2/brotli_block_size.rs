@@ -0,0 +1,93 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+/// [Brotli](https://github.com/google/brotli/) maximum input block size (`lgblock`).
+///
+/// Either automatic (the default), letting the encoder choose from the quality and window size,
+/// or the base-2 logarithm of the block size in bytes, from 16 (64 KiB) to 24 (16 MiB). Larger
+/// blocks give the encoder more context for its entropy codes. The encoder picks its own block
+/// size below quality 4.
+///
+/// Construct via [`TryFrom<u8>`] or [`FromStr`], where 0 means automatic and other values outside
+/// 16-24 are rejected. Converts into the `u8` or `i32` the `brotli` crate expects, 0 for
+/// automatic, via the corresponding `From` impls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BrotliBlockSize {
+    /// 0 for automatic
+    value: u8,
+}
+
+impl BrotliBlockSize {
+    pub const MIN: u8 = 16;
+    pub const MAX: u8 = 24;
+
+    /// Let the encoder choose the block size.
+    pub const AUTO: Self = Self { value: 0 };
+
+    pub fn is_auto(&self) -> bool {
+        self.value == 0
+    }
+}
+
+impl TryFrom<u8> for BrotliBlockSize {
+    type Error = Error;
+
+    fn try_from(lgblock: u8) -> Result<Self, Self::Error> {
+        if lgblock == 0 || (Self::MIN..=Self::MAX).contains(&lgblock) {
+            Ok(Self { value: lgblock })
+        } else {
+            Err(Error::InvalidOptions("lgblock must be 0 (automatic) or between 16 and 24"))
+        }
+    }
+}
+
+impl FromStr for BrotliBlockSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::AUTO),
+            _ => Self::try_from(s.parse::<u8>()?),
+        }
+    }
+}
+
+/// Formatted as the logarithm, or `auto`.
+impl fmt::Display for BrotliBlockSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            0 => write!(f, "auto"),
+            lgblock => write!(f, "{lgblock}"),
+        }
+    }
+}
+
+impl From<BrotliBlockSize> for u8 {
+    fn from(block_size: BrotliBlockSize) -> u8 {
+        block_size.value
+    }
+}
+
+impl From<BrotliBlockSize> for i32 {
+    fn from(block_size: BrotliBlockSize) -> i32 {
+        i32::from(block_size.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        assert!(BrotliBlockSize::default().is_auto());
+        assert_eq!(BrotliBlockSize::try_from(0).unwrap(), BrotliBlockSize::AUTO);
+        assert_eq!("auto".parse::<BrotliBlockSize>().unwrap(), BrotliBlockSize::AUTO);
+        assert_eq!(u8::from("24".parse::<BrotliBlockSize>().unwrap()), 24);
+        assert!(matches!(BrotliBlockSize::try_from(15), Err(Error::InvalidOptions(_))));
+        assert!(matches!(BrotliBlockSize::try_from(25), Err(Error::InvalidOptions(_))));
+        assert_eq!(BrotliBlockSize::AUTO.to_string(), "auto");
+        assert_eq!(BrotliBlockSize::try_from(18).unwrap().to_string(), "18");
+    }
+}
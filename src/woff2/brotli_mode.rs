@@ -0,0 +1,55 @@
+use std::{fmt, str::FromStr};
+
+use brotli::enc::backward_references::BrotliEncoderMode;
+
+use crate::Error;
+
+/// [Brotli](https://github.com/google/brotli/) compression mode, a hint about the kind of data
+/// being compressed.
+///
+/// Defaults to [`BrotliMode::Font`], which the WOFF2 reference encoder uses. The mode only tunes
+/// the encoder's heuristics; every mode produces a stream any Brotli decoder accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BrotliMode {
+    /// No assumptions about the data.
+    Generic,
+    /// UTF-8 text.
+    Text,
+    /// Font data.
+    #[default]
+    Font,
+}
+
+/// Parses `generic`, `text` or `font`.
+impl FromStr for BrotliMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "generic" => Ok(Self::Generic),
+            "text" => Ok(Self::Text),
+            "font" => Ok(Self::Font),
+            _ => Err(Error::InvalidOptions("mode must be generic, text or font")),
+        }
+    }
+}
+
+impl fmt::Display for BrotliMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Generic => "generic",
+            Self::Text => "text",
+            Self::Font => "font",
+        })
+    }
+}
+
+impl From<BrotliMode> for BrotliEncoderMode {
+    fn from(mode: BrotliMode) -> Self {
+        match mode {
+            BrotliMode::Generic => BrotliEncoderMode::BROTLI_MODE_GENERIC,
+            BrotliMode::Text => BrotliEncoderMode::BROTLI_MODE_TEXT,
+            BrotliMode::Font => BrotliEncoderMode::BROTLI_MODE_FONT,
+        }
+    }
+}
@@ -10,7 +10,7 @@ use crate::Error;
 /// Construct via [`From<u8>`] or [`FromStr`], and convert into the underlying
 /// `u8` or `i32` (as required by the `brotli` crate) via the corresponding
 /// `From` impls.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BrotliQuality {
     value: u8,
 }
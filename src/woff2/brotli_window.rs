@@ -0,0 +1,82 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+/// [Brotli](https://github.com/google/brotli/) sliding window size (`lgwin`).
+///
+/// The base-2 logarithm of the window size in bytes, from 10 (1 KiB) to 24 (16 MiB). Larger
+/// windows let Brotli find matches further back, which helps fonts of several megabytes such as
+/// CJK fonts. 24 is the largest window standard Brotli decoders, and so WOFF2 user agents,
+/// accept. Defaults to 22 (4 MiB).
+///
+/// Construct via [`TryFrom<u8>`] or [`FromStr`], which reject values outside 10-24, and convert
+/// into the underlying `u8` or `i32` via the corresponding `From` impls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrotliWindow {
+    value: u8,
+}
+
+impl BrotliWindow {
+    pub const MIN: u8 = 10;
+    pub const MAX: u8 = 24;
+}
+
+impl Default for BrotliWindow {
+    fn default() -> Self {
+        Self { value: 22 }
+    }
+}
+
+impl TryFrom<u8> for BrotliWindow {
+    type Error = Error;
+
+    fn try_from(lgwin: u8) -> Result<Self, Self::Error> {
+        if (Self::MIN..=Self::MAX).contains(&lgwin) {
+            Ok(Self { value: lgwin })
+        } else {
+            Err(Error::InvalidOptions("lgwin must be between 10 and 24"))
+        }
+    }
+}
+
+impl FromStr for BrotliWindow {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s.parse::<u8>()?)
+    }
+}
+
+impl fmt::Display for BrotliWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl From<BrotliWindow> for u8 {
+    fn from(window: BrotliWindow) -> u8 {
+        window.value
+    }
+}
+
+impl From<BrotliWindow> for i32 {
+    fn from(window: BrotliWindow) -> i32 {
+        i32::from(window.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(u8::from(BrotliWindow::default()), 22);
+        assert_eq!(u8::from(BrotliWindow::try_from(10).unwrap()), 10);
+        assert_eq!(u8::from(BrotliWindow::try_from(24).unwrap()), 24);
+        assert!(matches!(BrotliWindow::try_from(9), Err(Error::InvalidOptions(_))));
+        assert!(matches!(BrotliWindow::try_from(25), Err(Error::InvalidOptions(_))));
+        assert!(matches!("30".parse::<BrotliWindow>(), Err(Error::InvalidOptions(_))));
+        assert!(matches!("x".parse::<BrotliWindow>(), Err(Error::ParseInt(_))));
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    fs::{self, File},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use sha2::{Digest, Sha256};
+
+use super::{
+    encoder::{EncodeOptions, encode_with_options},
+    table_filter::TableFilter,
+};
+use crate::Error;
+
+const ENTRY_EXTENSION: &str = "woff2";
+const TEMP_EXTENSION: &str = "tmp";
+
+/// How old a temporary file must be before eviction treats its writer as crashed.
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// An on-disk cache of WOFF2 encodes, so that re-encoding an unchanged font with unchanged options
+/// skips the Brotli compression entirely.
+///
+/// Each entry is a WOFF2 file named after a SHA-256 hash of the crate version, the input bytes
+/// and every [`EncodeOptions`] field that affects the output. Upgrading the crate therefore
+/// never reuses older encodes. The progress and cancel hooks are not part of the key, and a
+/// cache hit reports no progress.
+///
+/// Once the entries exceed the size cap, the least recently used ones are removed. Several
+/// threads or processes may share a cache directory.
+///
+/// The cache is best-effort: an entry that cannot be read is a miss, and an encode that cannot
+/// be stored, e.g. in a read-only directory or on a full disk, is still returned.
+#[derive(Debug, Clone)]
+pub struct EncodeCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl EncodeCache {
+    /// The default size cap, 256 MiB.
+    pub const DEFAULT_MAX_BYTES: u64 = 256 << 20;
+
+    /// A cache in `dir`, capped at [`EncodeCache::DEFAULT_MAX_BYTES`]. The directory is created
+    /// when the first entry is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Cap the total size of the entries at `max_bytes`. Encodes larger than the cap are not
+    /// stored.
+    pub fn with_max_bytes(self, max_bytes: u64) -> Self {
+        Self { max_bytes, ..self }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Encode a font like [`encode_with_options`], returning the stored output if the same font
+    /// has been encoded with the same options before.
+    ///
+    /// An entry that is not a complete WOFF2 file, e.g. one truncated by a full disk, is encoded
+    /// again and replaced. Only encode errors are returned; cache I/O errors are ignored.
+    pub fn encode(&self, ttf_data: &[u8], options: EncodeOptions) -> Result<Vec<u8>, Error> {
+        let path = self
+            .dir
+            .join(format!("{}.{ENTRY_EXTENSION}", cache_key(ttf_data, &options)));
+        if let Some(woff2_data) = read_entry(&path) {
+            return Ok(woff2_data);
+        }
+        let woff2_data = encode_with_options(ttf_data, options)?;
+        // A failed store only costs the next call another encode
+        let _ = self.store(&path, &woff2_data);
+        Ok(woff2_data)
+    }
+
+    fn store(&self, path: &Path, woff2_data: &[u8]) -> Result<(), Error> {
+        static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+        if woff2_data.len() as u64 > self.max_bytes {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        // Write under a unique name first, so that no reader sees a partially written entry
+        let temp = self.dir.join(format!(
+            ".{}-{}.{TEMP_EXTENSION}",
+            process::id(),
+            NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temp, woff2_data)?;
+        if let Err(e) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        self.evict()
+    }
+
+    /// Remove the least recently used entries until they fit in the size cap, and temporary
+    /// files left behind by writers that crashed.
+    fn evict(&self) -> Result<(), Error> {
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            // Entries may be evicted by another process at any time
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let modified = metadata.modified()?;
+            match path.extension() {
+                Some(extension) if extension == ENTRY_EXTENSION => {
+                    entries.push((modified, metadata.len(), path));
+                }
+                // A younger temporary file may still be being written
+                Some(extension)
+                    if extension == TEMP_EXTENSION
+                        && now.duration_since(modified).unwrap_or_default() > STALE_TEMP_AGE =>
+                {
+                    let _ = fs::remove_file(&path);
+                }
+                _ => {}
+            }
+        }
+
+        let mut total: u64 = entries.iter().map(|&(_, len, _)| len).sum();
+        entries.sort_unstable();
+        for (_, len, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => total -= len,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a cache entry, or `None` on a miss or an unreadable entry.
+fn read_entry(path: &Path) -> Option<Vec<u8>> {
+    let woff2_data = fs::read(path).ok()?;
+    let complete = woff2_data.len() >= 12
+        && woff2_data.starts_with(b"wOF2")
+        && woff2_data[8..12] == (woff2_data.len() as u32).to_be_bytes();
+    if !complete {
+        return None;
+    }
+    // Mark the entry as recently used. A read-only cache still serves hits, only its eviction
+    // order goes stale.
+    let _ = File::options()
+        .append(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    Some(woff2_data)
+}
+
+/// The hex SHA-256 hash naming the cache entry of an encode.
+fn cache_key(ttf_data: &[u8], options: &EncodeOptions) -> String {
+    // Destructured so that a new option cannot be left out of the key by accident
+    let EncodeOptions {
+        quality,
+        lgwin,
+        lgblock,
+        mode,
+        transform_glyf_loca,
+        transform_hmtx,
+        threads,
+        metadata,
+        private_data,
+        verify_checksums,
+        subset,
+        strip_hinting,
+        table_filter,
+        progress: _,
+        cancel: _,
+    } = options;
+
+    let mut key = KeyHasher(Sha256::new());
+    key.bytes(env!("CARGO_PKG_VERSION").as_bytes());
+    key.bytes(ttf_data);
+    key.bytes(&[
+        u8::from(*quality),
+        u8::from(*lgwin),
+        u8::from(*lgblock),
+        u8::from(*transform_glyf_loca),
+        u8::from(*transform_hmtx),
+        u8::from(*verify_checksums),
+        u8::from(*strip_hinting),
+    ]);
+    key.bytes(mode.to_string().as_bytes());
+    // A single thread produces the same output as the single-threaded encoder
+    key.u64(threads.map_or(1, |threads| threads.get() as u64));
+    key.option(metadata.as_ref().map(String::as_bytes));
+    key.option(private_data.as_deref());
+    key.u64(u64::from(subset.is_some()));
+    if let Some(subset) = subset {
+        key.u64(subset.unicodes.len() as u64);
+        for range in &subset.unicodes {
+            key.u64(u64::from(*range.start()));
+            key.u64(u64::from(*range.end()));
+        }
+        key.u64(subset.glyph_ids.len() as u64);
+        for range in &subset.glyph_ids {
+            key.u64(u64::from(*range.start()));
+            key.u64(u64::from(*range.end()));
+        }
+    }
+    match table_filter {
+        None => key.u64(0),
+        Some(filter) => {
+            let (kind, tags) = match filter {
+                TableFilter::Drop(tags) => (1, tags),
+                TableFilter::Keep(tags) => (2, tags),
+            };
+            key.u64(kind);
+            key.bytes(&tags.iter().flat_map(|tag| tag.0).collect::<Vec<_>>());
+        }
+    }
+
+    key.0.finalize().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Feeds values into the key hash with unambiguous framing: variable-length values are prefixed
+/// with their length, so that e.g. moving a byte from the metadata to the private data changes
+/// the key.
+struct KeyHasher(Sha256);
+
+impl KeyHasher {
+    fn u64(&mut self, value: u64) {
+        self.0.update(value.to_be_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.u64(bytes.len() as u64);
+        self.0.update(bytes);
+    }
+
+    fn option(&mut self, bytes: Option<&[u8]>) {
+        match bytes {
+            None => self.u64(0),
+            Some(bytes) => {
+                self.u64(1);
+                self.bytes(bytes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BrotliQuality, SubsetSpec, Tag};
+
+    #[test]
+    fn test_cache_key_covers_options() {
+        let ttf_data = b"font";
+        let base = cache_key(ttf_data, &EncodeOptions::default());
+        assert_eq!(base.len(), 64);
+        assert_eq!(base, cache_key(ttf_data, &EncodeOptions::default()));
+        assert_ne!(base, cache_key(b"font2", &EncodeOptions::default()));
+
+        let variants = [
+            EncodeOptions {
+                quality: BrotliQuality::from(5),
+                ..EncodeOptions::default()
+            },
+            EncodeOptions { transform_hmtx: true, ..EncodeOptions::default() },
+            EncodeOptions {
+                metadata: Some(String::new()),
+                ..EncodeOptions::default()
+            },
+            EncodeOptions {
+                private_data: Some(Vec::new()),
+                ..EncodeOptions::default()
+            },
+            EncodeOptions {
+                subset: Some(SubsetSpec::default()),
+                ..EncodeOptions::default()
+            },
+            EncodeOptions {
+                table_filter: Some(TableFilter::Drop(vec![Tag(*b"DSIG")])),
+                ..EncodeOptions::default()
+            },
+            EncodeOptions {
+                table_filter: Some(TableFilter::Keep(vec![Tag(*b"DSIG")])),
+                ..EncodeOptions::default()
+            },
+        ];
+        let mut keys: Vec<_> =
+            variants.iter().map(|options| cache_key(ttf_data, options)).collect();
+        keys.push(base);
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), variants.len() + 1);
+
+        // Hooks and a single thread do not change the output
+        let hooked = EncodeOptions {
+            threads: std::num::NonZeroUsize::new(1),
+            cancel: Some(crate::CancelHook::new(|| false)),
+            ..EncodeOptions::default()
+        };
+        assert_eq!(cache_key(ttf_data, &hooked), cache_key(ttf_data, &EncodeOptions::default()));
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    borrow::Cow,
+    io::{Cursor, Read},
+};
+
+use brotli::Decompressor;
+use byteorder::{BigEndian, ReadBytesExt};
+
+use super::{
+    directory::TableDirectoryEntry,
+    header::Woff2Header,
+    hmtx::HmtxContext,
+    sfnt::{COLLECTION_FLAVOR, write_collection, write_sfnt},
+    tag::Tag,
+    transform::ReconstructedGlyf,
+    varint::decode_255_u_int16,
+};
+use crate::{Error, Error::DataTooShort};
+
+/// The WOFF2 collection directory: the collection version and, for each font, its flavor and
+/// the indices of its tables in the table directory.
+pub(super) struct CollectionDirectory {
+    pub version: u32,
+    pub fonts: Vec<(u32, Vec<usize>)>,
+}
+
+impl CollectionDirectory {
+    fn read(cursor: &mut Cursor<&[u8]>, num_tables: usize) -> Result<Self, Error> {
+        let version = cursor
+            .read_u32::<BigEndian>()
+            .map_err(|_| DataTooShort { context: "collection directory" })?;
+        let num_fonts = decode_255_u_int16(cursor)?;
+
+        let mut fonts = Vec::with_capacity(num_fonts as usize);
+        for _ in 0..num_fonts {
+            let font_num_tables = decode_255_u_int16(cursor)?;
+            let flavor = cursor
+                .read_u32::<BigEndian>()
+                .map_err(|_| DataTooShort { context: "collection directory" })?;
+            let indices = (0..font_num_tables)
+                .map(|_| match decode_255_u_int16(cursor)? as usize {
+                    index if index < num_tables => Ok(index),
+                    _ => Err(Error::InvalidWoff2("collection table index out of range")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            fonts.push((flavor, indices));
+        }
+
+        Ok(Self { version, fonts })
+    }
+}
+
+/// Largest decompressed size accepted from a file's declared lengths, as in the reference decoder.
+pub(super) const MAX_DECOMPRESSED_SIZE: u64 = 30 * 1024 * 1024;
+
+/// Decompress a Brotli stream that must inflate to exactly `expected` bytes.
+fn decompress(compressed: &[u8], expected: u64) -> Result<Vec<u8>, Error> {
+    if expected > MAX_DECOMPRESSED_SIZE {
+        return Err(Error::InvalidWoff2("decompressed size exceeds 30 MB"));
+    }
+    // The declared size comes from the file, so only trust it as far as the compressed data
+    // could plausibly expand.
+    let mut data = Vec::with_capacity(expected.min(compressed.len() as u64 * 8) as usize);
+    // Read one byte past the expected size so an oversized stream is detected without inflating
+    // all of it.
+    Decompressor::new(compressed, 4096)
+        .take(expected + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| Error::Decompression(e.to_string()))?;
+
+    if data.len() as u64 != expected {
+        return Err(Error::InvalidWoff2("decompressed size does not match expected size"));
+    }
+    Ok(data)
+}
+
+/// A WOFF2 file with its header, table directory and collection directory parsed.
+pub(super) struct Decoder<'a> {
+    data: &'a [u8],
+    pub header: Woff2Header,
+    pub entries: Vec<TableDirectoryEntry>,
+    pub collection: Option<CollectionDirectory>,
+    font_data_offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Result<Self, Error> {
+        let header = Woff2Header::try_from(data)?;
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(48);
+        let entries = (0..header.num_tables)
+            .map(|_| TableDirectoryEntry::try_from(&mut cursor))
+            .collect::<Result<Vec<_>, _>>()?;
+        let collection = (header.flavor == COLLECTION_FLAVOR)
+            .then(|| CollectionDirectory::read(&mut cursor, entries.len()))
+            .transpose()?;
+        let font_data_offset = cursor.position() as usize;
+
+        Ok(Self {
+            data,
+            header,
+            entries,
+            collection,
+            font_data_offset,
+        })
+    }
+
+    fn decompress(&self) -> Result<Vec<u8>, Error> {
+        let start = self.font_data_offset;
+        let end = start + self.header.total_compressed_size as usize;
+        let compressed = self
+            .data
+            .get(start..end)
+            .ok_or(DataTooShort { context: "compressed font data" })?;
+
+        let expected: u64 = self.entries.iter().map(|e| u64::from(e.stored_length())).sum();
+        decompress(compressed, expected)
+    }
+
+    /// The table directory indices of the (first) font that contains the table at `index`.
+    fn font_tables(&self, index: usize) -> Option<Cow<'_, [usize]>> {
+        match &self.collection {
+            Some(collection) => collection
+                .fonts
+                .iter()
+                .find(|(_, indices)| indices.contains(&index))
+                .map(|(_, indices)| Cow::Borrowed(indices.as_slice())),
+            None => Some(Cow::Owned((0..self.entries.len()).collect())),
+        }
+    }
+}
+
+impl TryFrom<Decoder<'_>> for Vec<u8> {
+    type Error = Error;
+
+    fn try_from(decoder: Decoder<'_>) -> Result<Self, Self::Error> {
+        let font_data = decoder.decompress()?;
+
+        let mut tables = Vec::with_capacity(decoder.entries.len());
+        let mut pending_loca = None;
+        // xMin of every glyph, for each transformed glyf table
+        let mut x_mins = vec![None; decoder.entries.len()];
+        let mut transformed_hmtx = Vec::new();
+        let mut offset = 0usize;
+        for (index, entry) in decoder.entries.iter().enumerate() {
+            let length = entry.stored_length() as usize;
+            let data = &font_data[offset..offset + length];
+            offset += length;
+
+            let table = match entry.transform_version {
+                0 if entry.tag.is_glyf() => {
+                    let ReconstructedGlyf { glyf, loca, x_mins: glyph_x_mins } = data.try_into()?;
+                    if pending_loca.replace(loca).is_some() {
+                        return Err(Error::InvalidWoff2("transformed glyf without loca"));
+                    }
+                    x_mins[index] = Some(glyph_x_mins);
+                    Cow::Owned(glyf)
+                }
+                0 if entry.tag.is_loca() => {
+                    // WOFF2 requires a transformed loca to immediately follow its glyf
+                    let loca = pending_loca
+                        .take()
+                        .ok_or(Error::InvalidWoff2("transformed loca without glyf"))?;
+                    if loca.len() != entry.orig_length as usize {
+                        return Err(Error::InvalidWoff2("loca length mismatch"));
+                    }
+                    Cow::Owned(loca)
+                }
+                // The loca rebuilt with a transformed glyf must replace the font's own
+                _ if entry.tag.is_loca() && pending_loca.is_some() => {
+                    return Err(Error::InvalidWoff2("transformed glyf with untransformed loca"));
+                }
+                1 if entry.tag.is_hmtx() => {
+                    // Rebuilt below, once the font's glyf and hhea tables are available
+                    transformed_hmtx.push(index);
+                    Cow::Borrowed(data)
+                }
+                _ if entry.transform_length.is_some() => {
+                    return Err(Error::InvalidWoff2("unsupported table transform"));
+                }
+                _ => Cow::Borrowed(data),
+            };
+            tables.push((entry.tag, table));
+        }
+        if pending_loca.is_some() {
+            return Err(Error::InvalidWoff2("transformed glyf without loca"));
+        }
+
+        for index in transformed_hmtx {
+            let font_tables = decoder
+                .font_tables(index)
+                .ok_or(Error::InvalidWoff2("transformed hmtx not referenced by any font"))?;
+            let find = |tag| font_tables.iter().copied().find(|&i| decoder.entries[i].tag == tag);
+            let glyph_x_mins = find(Tag::GLYF)
+                .and_then(|glyf| x_mins[glyf].as_deref())
+                .ok_or(Error::InvalidWoff2("transformed hmtx without transformed glyf"))?;
+            let hhea =
+                find(Tag::HHEA).ok_or(Error::InvalidWoff2("transformed hmtx without hhea"))?;
+
+            let context = HmtxContext { hhea: &tables[hhea].1, x_mins: glyph_x_mins };
+            let hmtx = context.reconstruct(&tables[index].1)?;
+            if hmtx.len() != decoder.entries[index].orig_length as usize {
+                return Err(Error::InvalidWoff2("hmtx length mismatch"));
+            }
+            tables[index].1 = Cow::Owned(hmtx);
+        }
+
+        let tables: Vec<_> = tables.iter().map(|(tag, data)| (*tag, data.as_ref())).collect();
+        Ok(match &decoder.collection {
+            Some(collection) => write_collection(collection.version, &collection.fonts, &tables),
+            None => write_sfnt(decoder.header.flavor, &tables),
+        })
+    }
+}
+
+/// Decode a WOFF2 font back into an SFNT (TTF or OTF) font.
+///
+/// WOFF2 collections are decoded back into a TrueType collection (`.ttc`), with tables shared
+/// between fonts written once.
+///
+/// The Brotli-compressed font data is decompressed and the tables are written into a new SFNT
+/// with freshly computed table checksums and `head.checkSumAdjustment`. Transformed `glyf`/`loca`
+/// tables are rebuilt from their glyph streams; the result describes the same outlines,
+/// instructions and bounding boxes as the original, though the glyph encoding (flag repeats,
+/// coordinate widths, padding) may differ. A transformed `hmtx` table is rebuilt byte for byte,
+/// with the dropped left side bearings restored from the glyphs' `xMin`. All other tables are
+/// preserved byte for byte, but their order and padding in the output may differ from the font
+/// that was originally encoded.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a valid WOFF2 file, the Brotli stream is corrupt or
+/// its size does not match the table directory, or the file uses a table transformation that is
+/// not supported.
+pub fn decode(woff2_data: &[u8]) -> Result<Vec<u8>, Error> {
+    Decoder::new(woff2_data)?.try_into()
+}
+
+/// Extract the [extended metadata block](https://www.w3.org/TR/WOFF2/#Metadata) from a WOFF2 file.
+///
+/// Returns `None` when the file has no metadata block.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a valid WOFF2 file, the metadata block lies outside
+/// the file, or it does not decompress to UTF-8 text of the declared length.
+pub fn decode_metadata(woff2_data: &[u8]) -> Result<Option<String>, Error> {
+    read_metadata(woff2_data, &Woff2Header::try_from(woff2_data)?)
+}
+
+/// Extract the [private data block](https://www.w3.org/TR/WOFF2/#Private) from a WOFF2 file.
+///
+/// Returns `None` when the file has no private data block.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a valid WOFF2 file or the private data block lies
+/// outside the file.
+pub fn decode_private_data(woff2_data: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let header = Woff2Header::try_from(woff2_data)?;
+    Ok(read_private_data(woff2_data, &header)?.map(<[u8]>::to_vec))
+}
+
+pub(super) fn read_metadata(data: &[u8], header: &Woff2Header) -> Result<Option<String>, Error> {
+    if header.meta_length == 0 {
+        return Ok(None);
+    }
+    let start = header.meta_offset as usize;
+    let compressed = data
+        .get(start..start + header.meta_length as usize)
+        .ok_or(Error::InvalidWoff2("metadata block exceeds file"))?;
+    let xml = decompress(compressed, u64::from(header.meta_orig_length))?;
+    String::from_utf8(xml)
+        .map(Some)
+        .map_err(|_| Error::InvalidWoff2("metadata is not valid UTF-8"))
+}
+
+pub(super) fn read_private_data<'a>(
+    data: &'a [u8],
+    header: &Woff2Header,
+) -> Result<Option<&'a [u8]>, Error> {
+    if header.priv_length == 0 {
+        return Ok(None);
+    }
+    let start = header.priv_offset as usize;
+    data.get(start..start + header.priv_length as usize)
+        .map(Some)
+        .ok_or(Error::InvalidWoff2("private data block exceeds file"))
+}
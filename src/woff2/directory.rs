@@ -1,4 +1,13 @@
-use super::{inline_bytes::InlineBytes, tag::Tag, varint::encode_base128};
+use std::io::{Cursor, Read};
+
+use byteorder::ReadBytesExt;
+
+use super::{
+    inline_bytes::InlineBytes,
+    tag::Tag,
+    varint::{decode_base128, encode_base128},
+};
+use crate::Error;
 
 pub(crate) struct TableDirectoryEntry {
     pub tag: Tag,
@@ -7,6 +16,25 @@ pub(crate) struct TableDirectoryEntry {
     pub transform_length: Option<u32>,
 }
 
+impl TableDirectoryEntry {
+    /// Whether the entry carries a `transformLength` field.
+    ///
+    /// Version 3 is the null transform for `glyf`/`loca`; version 0 is the null transform for
+    /// every other table.
+    fn has_transform_length(&self) -> bool {
+        if self.tag.is_glyf() || self.tag.is_loca() {
+            self.transform_version != 3
+        } else {
+            self.transform_version != 0
+        }
+    }
+
+    /// Number of bytes the table occupies in the decompressed font data stream.
+    pub fn stored_length(&self) -> u32 {
+        self.transform_length.unwrap_or(self.orig_length)
+    }
+}
+
 impl From<&TableDirectoryEntry> for InlineBytes<15> {
     fn from(entry: &TableDirectoryEntry) -> Self {
         let mut data = [0u8; 15];
@@ -26,9 +54,7 @@ impl From<&TableDirectoryEntry> for InlineBytes<15> {
         data[len..len + orig_slice.len()].copy_from_slice(orig_slice);
         len += orig_slice.len();
 
-        let is_glyf_or_loca = entry.tag.is_glyf() || entry.tag.is_loca();
-        if is_glyf_or_loca
-            && entry.transform_version == 0
+        if entry.has_transform_length()
             && let Some(tlen) = entry.transform_length
         {
             let tlen_bytes = encode_base128(tlen);
@@ -40,3 +66,93 @@ impl From<&TableDirectoryEntry> for InlineBytes<15> {
         InlineBytes::new(data, len as u8)
     }
 }
+
+impl TryFrom<&mut Cursor<&[u8]>> for TableDirectoryEntry {
+    type Error = Error;
+
+    fn try_from(cursor: &mut Cursor<&[u8]>) -> Result<Self, Self::Error> {
+        let flags = cursor
+            .read_u8()
+            .map_err(|_| Error::DataTooShort { context: "table directory" })?;
+        let transform_version = flags >> 6;
+        let tag = match flags & 0x3F {
+            63 => {
+                let mut tag_bytes = [0u8; 4];
+                cursor
+                    .read_exact(&mut tag_bytes)
+                    .map_err(|_| Error::DataTooShort { context: "table directory" })?;
+                Tag(tag_bytes)
+            }
+            index => {
+                Tag::from_known_index(index).ok_or(Error::InvalidWoff2("unknown tag index"))?
+            }
+        };
+
+        let orig_length = decode_base128(cursor)?;
+        let mut entry = Self {
+            tag,
+            orig_length,
+            transform_version,
+            transform_length: None,
+        };
+        if entry.has_transform_length() {
+            entry.transform_length = Some(decode_base128(cursor)?);
+        }
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(entry: &TableDirectoryEntry) -> TableDirectoryEntry {
+        let encoded = InlineBytes::<15>::from(entry);
+        let mut cursor = Cursor::new(encoded.as_slice());
+        let decoded = TableDirectoryEntry::try_from(&mut cursor).unwrap();
+        assert_eq!(cursor.position() as usize, encoded.len());
+        decoded
+    }
+
+    #[test]
+    fn test_known_tag_roundtrip() {
+        let entry = TableDirectoryEntry {
+            tag: Tag::GLYF,
+            orig_length: 123456,
+            transform_version: 0,
+            transform_length: Some(65432),
+        };
+        let decoded = roundtrip(&entry);
+        assert_eq!(decoded.tag, Tag::GLYF);
+        assert_eq!(decoded.orig_length, 123456);
+        assert_eq!(decoded.transform_version, 0);
+        assert_eq!(decoded.transform_length, Some(65432));
+        assert_eq!(decoded.stored_length(), 65432);
+    }
+
+    #[test]
+    fn test_custom_tag_roundtrip() {
+        let entry = TableDirectoryEntry {
+            tag: Tag(*b"STAT"),
+            orig_length: 42,
+            transform_version: 0,
+            transform_length: None,
+        };
+        let decoded = roundtrip(&entry);
+        assert_eq!(decoded.tag, Tag(*b"STAT"));
+        assert_eq!(decoded.transform_length, None);
+        assert_eq!(decoded.stored_length(), 42);
+    }
+
+    #[test]
+    fn test_null_transform_glyf_has_no_transform_length() {
+        let entry = TableDirectoryEntry {
+            tag: Tag::LOCA,
+            orig_length: 100,
+            transform_version: 3,
+            transform_length: None,
+        };
+        assert_eq!(InlineBytes::<15>::from(&entry).len(), 2);
+        assert_eq!(roundtrip(&entry).transform_length, None);
+    }
+}
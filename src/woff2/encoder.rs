@@ -1,4 +1,10 @@
-use std::num::NonZeroUsize;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    io::{self, Read, Write},
+    num::NonZeroUsize,
+    sync::{Arc, atomic::AtomicBool},
+};
 
 use brotli::enc::{
     BrotliCompress, BrotliEncoderParams, StandardAlloc,
@@ -8,20 +14,39 @@ use brotli::enc::{
 };
 
 use super::{
+    brotli_block_size::BrotliBlockSize,
+    brotli_mode::BrotliMode,
     brotli_quality::BrotliQuality,
+    brotli_window::BrotliWindow,
     directory::TableDirectoryEntry,
     header::{WOFF2_SIGNATURE, Woff2Header},
+    hinting::strip_hinting,
+    hmtx::HmtxContext,
+    hooks::{CancelHook, Hooks, Progress, ProgressHook},
     inline_bytes::InlineBytes,
-    sfnt::{Sfnt, SfntTable},
-    transform::GlyfContext,
+    report::{EncodeReport, TableReport},
+    sfnt::{COLLECTION_FLAVOR, FontFile, SfntTable},
+    subset::{SubsetSpec, subset},
+    table_filter::TableFilter,
+    transform::{GlyfContext, glyf_streams},
+    varint::encode_255_u_int16,
+    woff1::{is_woff1, read_woff1},
 };
 use crate::Error;
 
 /// Options for WOFF2 encoding.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct EncodeOptions {
     /// Brotli compression quality (0-11). Higher values produce smaller output but take longer.
     pub quality: BrotliQuality,
+    /// Brotli sliding window size. Fonts of several megabytes, such as CJK fonts, usually
+    /// compress noticeably better with the 16 MiB maximum than with the default 4 MiB window.
+    pub lgwin: BrotliWindow,
+    /// Brotli maximum input block size, chosen by the encoder by default.
+    pub lgblock: BrotliBlockSize,
+    /// Brotli compression mode for the font data. The extended metadata block is always
+    /// compressed in text mode.
+    pub mode: BrotliMode,
     /// Apply the WOFF2 `glyf`/`loca` table transformation.
     ///
     /// When enabled, the `glyf` and `loca` tables are restructured per the
@@ -30,6 +55,15 @@ pub struct EncodeOptions {
     /// The transformation is only applied when all of `glyf`, `loca`, `head`,
     /// and `maxp` tables are present; otherwise the tables are stored as-is.
     pub transform_glyf_loca: bool,
+    /// Apply the WOFF2 `hmtx` table transformation (transform version 1).
+    ///
+    /// When enabled, left side bearings that equal the glyph's `xMin` are dropped from `hmtx`,
+    /// which saves noticeable bytes on fonts with many glyphs. The transformation is only
+    /// applied alongside the `glyf`/`loca` transformation, when an `hhea` table is present and
+    /// at least one of the `hmtx` side bearing arrays can be dropped entirely.
+    ///
+    /// Disabled by default, matching the reference encoders.
+    pub transform_hmtx: bool,
     /// Number of threads to use for the Brotli compression step.
     ///
     /// `None` (default) uses the single-threaded encoder, which is fully deterministic.
@@ -48,66 +82,356 @@ pub struct EncodeOptions {
     /// available on WebAssembly. Setting `Some(n > 1)` from a WASM build is
     /// safe (no panic) but yields the same output as `None`.
     pub threads: Option<NonZeroUsize>,
+    /// XML to embed as the WOFF2 [extended metadata block](https://www.w3.org/TR/WOFF2/#Metadata),
+    /// e.g. licensing information.
+    ///
+    /// The XML is Brotli-compressed separately from the font data and stored after it. It is
+    /// embedded as given; no validation against the WOFF metadata schema is performed.
+    ///
+    /// When the input is a WOFF 1.0 file, its own metadata block is carried over unless this is
+    /// set.
+    pub metadata: Option<String>,
+    /// Opaque data to embed as the WOFF2 [private data block](https://www.w3.org/TR/WOFF2/#Private),
+    /// e.g. a build ID or source hash.
+    ///
+    /// The data is stored uncompressed at the end of the file and is ignored by user agents.
+    /// When the input is a WOFF 1.0 file, its own private data block is carried over unless this
+    /// is set.
+    pub private_data: Option<Vec<u8>>,
+    /// Verify the input's table checksums and `head.checkSumAdjustment` before encoding.
+    ///
+    /// A mismatch fails the encode with [`Error::ChecksumMismatch`]. The whole-font
+    /// `checkSumAdjustment` is only checked for single fonts, since a `head` table shared by the
+    /// fonts of a collection cannot hold a correct adjustment for each of them.
+    ///
+    /// Disabled by default: many fonts in the wild carry stale checksums, and decoders ignore
+    /// them.
+    pub verify_checksums: bool,
+    /// Subset the font to the given characters and glyphs before encoding.
+    ///
+    /// Only single TrueType-flavored fonts without variations can be subset. Tables that refer to
+    /// glyph IDs but are not rewritten by the subsetter, such as `GSUB`, `GPOS` and `kern`, are
+    /// dropped; see [`SubsetSpec`].
+    pub subset: Option<SubsetSpec>,
+    /// Remove the TrueType hinting before encoding, after subsetting.
+    ///
+    /// The instructions of every glyph are emptied and the `fpgm`, `prep`, `cvt `, `cvar`,
+    /// `hdmx`, `LTSH` and `VDMX` tables are dropped, with the instruction limits in `maxp` and the
+    /// instruction flags in `head` reset to match. Unhinted fonts are noticeably smaller and
+    /// render the same in browsers that ignore hinting, as most do on high-DPI displays. CFF
+    /// hints are left untouched.
+    pub strip_hinting: bool,
+    /// Encode only some of the font's tables, e.g. to drop a `DSIG` signature that any change to
+    /// the font invalidates anyway.
+    ///
+    /// The filter applies after subsetting and hinting removal, and the WOFF2 table directory,
+    /// `numTables` and `totalSfntSize` describe the remaining tables. No check is made that the
+    /// remaining tables still form a usable font.
+    pub table_filter: Option<TableFilter>,
+    /// Called as the encode progresses: every few hundred glyphs while transforming `glyf`, and
+    /// after each Brotli metablock. See [`Progress`].
+    ///
+    /// Multi-threaded compression ([`EncodeOptions::threads`]) only reports once it is done.
+    pub progress: Option<ProgressHook>,
+    /// Polled at the same points as [`EncodeOptions::progress`], and between encoding steps;
+    /// returning `true` aborts the encode with [`Error::Cancelled`].
+    pub cancel: Option<CancelHook>,
 }
 
 impl Default for EncodeOptions {
     fn default() -> Self {
         Self {
             quality: BrotliQuality::default(),
+            lgwin: BrotliWindow::default(),
+            lgblock: BrotliBlockSize::default(),
+            mode: BrotliMode::default(),
             transform_glyf_loca: true,
+            transform_hmtx: false,
             threads: None,
+            metadata: None,
+            private_data: None,
+            verify_checksums: false,
+            subset: None,
+            strip_hinting: false,
+            table_filter: None,
+            progress: None,
+            cancel: None,
         }
     }
 }
 
-struct Encoder<'a> {
-    data: &'a [u8],
-    sfnt: Sfnt,
+pub(super) struct Encoder<'a> {
+    /// The input font, or the font rebuilt from it by unpacking WOFF 1.0, subsetting or stripping
+    /// hinting
+    data: Cow<'a, [u8]>,
+    font: FontFile,
     options: EncodeOptions,
+    /// Set from another thread to abandon the encode at the next check
+    cancelled: Option<Arc<AtomicBool>>,
 }
 
-struct TableRefs<'a> {
-    glyf: Option<&'a SfntTable>,
-    loca: Option<&'a SfntTable>,
-    head: Option<&'a SfntTable>,
-    maxp: Option<&'a SfntTable>,
+/// Positions of the tables used by the `glyf`/`loca` and `hmtx` transforms within the directory
+/// table list.
+struct TableRefs {
+    glyf: Option<usize>,
+    loca: Option<usize>,
+    head: Option<usize>,
+    maxp: Option<usize>,
+    hhea: Option<usize>,
+    hmtx: Option<usize>,
 }
 
-impl<'a> TableRefs<'a> {
-    fn from_sorted(sorted_tables: &[&'a SfntTable]) -> Self {
-        let mut refs = Self { glyf: None, loca: None, head: None, maxp: None };
-        for &table in sorted_tables {
-            if table.tag.is_glyf() {
-                refs.glyf = Some(table);
-            } else if table.tag.is_loca() {
-                refs.loca = Some(table);
-            } else if table.tag.is_head() {
-                refs.head = Some(table);
-            } else if table.tag.is_maxp() {
-                refs.maxp = Some(table);
+impl TableRefs {
+    fn new(tables: &[&SfntTable], font_indices: &[usize]) -> Self {
+        let mut refs = Self {
+            glyf: None,
+            loca: None,
+            head: None,
+            maxp: None,
+            hhea: None,
+            hmtx: None,
+        };
+        for &index in font_indices {
+            let tag = tables[index].tag;
+            if tag.is_glyf() {
+                refs.glyf = Some(index);
+            } else if tag.is_loca() {
+                refs.loca = Some(index);
+            } else if tag.is_head() {
+                refs.head = Some(index);
+            } else if tag.is_maxp() {
+                refs.maxp = Some(index);
+            } else if tag.is_hhea() {
+                refs.hhea = Some(index);
+            } else if tag.is_hmtx() {
+                refs.hmtx = Some(index);
             }
         }
         refs
     }
 }
 
+pub(super) fn align4(value: u32) -> u32 {
+    (value + 3) & !3
+}
+
+/// The Brotli-compressed extended metadata block.
+struct CompressedMetadata {
+    data: Vec<u8>,
+    orig_length: u32,
+}
+
+/// The pieces of a WOFF2 file that follow its header, along with the header fields that
+/// describe the font.
+struct EncodedFont {
+    num_tables: u16,
+    total_sfnt_size: u32,
+    version: (u16, u16),
+    directory: Vec<u8>,
+    compressed_data: Vec<u8>,
+    metadata: Option<CompressedMetadata>,
+}
+
+/// Reads a sequence of slices back to back, so the stored tables can be fed to Brotli without
+/// first concatenating them.
+struct ChainedSlices<'s> {
+    slices: std::slice::Iter<'s, &'s [u8]>,
+    current: &'s [u8],
+    /// Bytes read so far
+    consumed: &'s Cell<u64>,
+}
+
+impl Read for ChainedSlices<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.slices.next() {
+                Some(slice) => self.current = slice,
+                None => return Ok(0),
+            }
+        }
+        let read = self.current.read(buf)?;
+        self.consumed.set(self.consumed.get() + read as u64);
+        Ok(read)
+    }
+}
+
+/// Collects Brotli's output, running the hooks whenever output follows new input, i.e. once per
+/// metablock.
+struct HookedOutput<'s> {
+    data: Vec<u8>,
+    hooks: Hooks<'s>,
+    /// Bytes of input Brotli has read so far
+    consumed: &'s Cell<u64>,
+    reported: u64,
+    total: u64,
+    /// The error returned by the hooks, which fails the write to stop Brotli
+    error: Option<Error>,
+}
+
+impl Write for HookedOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let consumed = self.consumed.get();
+        if consumed != self.reported {
+            self.reported = consumed;
+            let progress = Progress::Compressed { done: consumed, total: self.total };
+            if let Err(e) = self.hooks.report(progress) {
+                self.error = Some(e);
+                return Err(io::Error::other("stopped by hook"));
+            }
+        }
+        self.data.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A table as it is stored in the WOFF2 font data stream.
+struct StoredTable<'t> {
+    table: &'t SfntTable,
+    transform_version: u8,
+    /// Transformed table data, or `None` when the table is stored as-is.
+    transformed: Option<Vec<u8>>,
+}
+
 impl<'a> Encoder<'a> {
-    fn new(data: &'a [u8], options: EncodeOptions) -> Result<Self, Error> {
-        let sfnt: Sfnt = data.try_into()?;
-        Ok(Self { data, sfnt, options })
+    pub(super) fn new(data: &'a [u8], mut options: EncodeOptions) -> Result<Self, Error> {
+        let mut data = Cow::Borrowed(data);
+        if is_woff1(&data) {
+            let woff = read_woff1(&data, options.verify_checksums)?;
+            options.metadata = options.metadata.or(woff.metadata);
+            options.private_data = options.private_data.or(woff.private_data);
+            data = Cow::Owned(woff.sfnt);
+        }
+        let mut font = FontFile::try_from(&data[..])?;
+        if options.verify_checksums {
+            font.verify_checksums(&data)?;
+        }
+        if let Some(spec) = &options.subset {
+            data = Cow::Owned(subset(&data, &font, spec)?);
+            font = FontFile::try_from(&data[..])?;
+        }
+        if options.strip_hinting {
+            data = Cow::Owned(strip_hinting(&data, &font)?);
+            font = FontFile::try_from(&data[..])?;
+        }
+        if let Some(filter) = &options.table_filter {
+            font.retain_tables(|table| filter.keeps(table.tag));
+            if font.fonts().iter().any(|sfnt| sfnt.tables.is_empty()) {
+                return Err(Error::NoTables);
+            }
+        }
+        Ok(Self { data, font, options, cancelled: None })
+    }
+
+    /// Abandon the encode with [`Error::Cancelled`] once `cancelled` is set, checked wherever
+    /// [`EncodeOptions::cancel`] is polled.
+    #[cfg(feature = "async")]
+    pub(super) fn with_cancel_flag(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
     }
 
-    fn table_slice(&self, table: &SfntTable) -> &'a [u8] {
+    fn hooks(&self) -> Hooks<'_> {
+        Hooks {
+            progress: self.options.progress.as_ref(),
+            cancel: self.options.cancel.as_ref(),
+            cancelled: self.cancelled.as_deref(),
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<(), Error> {
+        self.hooks().check_cancelled()
+    }
+
+    /// An encoder for the same font with other settings, sharing the font data.
+    ///
+    /// Subsetting, hinting removal and the table filter have already been applied to the font,
+    /// so those options are ignored.
+    pub(super) fn with_options(&self, options: EncodeOptions) -> Encoder<'_> {
+        Encoder {
+            data: Cow::Borrowed(&self.data),
+            font: self.font.clone(),
+            options,
+            cancelled: self.cancelled.clone(),
+        }
+    }
+
+    fn brotli_params(&self, size_hint: usize) -> BrotliEncoderParams {
+        BrotliEncoderParams {
+            quality: self.options.quality.into(),
+            lgwin: self.options.lgwin.into(),
+            lgblock: self.options.lgblock.into(),
+            mode: self.options.mode.into(),
+            size_hint,
+            ..Default::default()
+        }
+    }
+
+    /// The font, after subsetting, hinting removal and table filtering.
+    pub(super) fn font(&self) -> &FontFile {
+        &self.font
+    }
+
+    pub(super) fn options(&self) -> &EncodeOptions {
+        &self.options
+    }
+
+    pub(super) fn table_slice(&self, table: &SfntTable) -> &[u8] {
         let start = table.offset as usize;
         let end = start + table.length as usize;
         &self.data[start..end]
     }
 
-    fn extract_version(&self, table_refs: &TableRefs) -> (u16, u16) {
-        table_refs
+    /// Collect the tables in WOFF2 table directory order, along with the positions of each
+    /// font's tables in that list.
+    ///
+    /// Tables are sorted by tag within each font, with `loca` moved to immediately follow `glyf`
+    /// as the WOFF2 spec requires. In a collection, a table shared by several fonts (same tag,
+    /// offset and length) is listed once.
+    fn directory_order(&self) -> (Vec<&SfntTable>, Vec<Vec<usize>>) {
+        let fonts = self.font.fonts();
+        let mut tables: Vec<&SfntTable> = Vec::new();
+        let mut font_indices = Vec::with_capacity(fonts.len());
+
+        for sfnt in fonts {
+            let mut sorted_tables: Vec<_> = sfnt.tables.iter().collect();
+            sorted_tables.sort_by_key(|t| t.tag);
+
+            // WOFF2 spec requires loca to immediately follow glyf in the table directory
+            if let Some(glyf_pos) = sorted_tables.iter().position(|t| t.tag.is_glyf())
+                && let Some(loca_pos) = sorted_tables.iter().position(|t| t.tag.is_loca())
+                && loca_pos != glyf_pos + 1
+            {
+                let loca = sorted_tables.remove(loca_pos);
+                let new_glyf_pos = sorted_tables.iter().position(|t| t.tag.is_glyf()).unwrap();
+                sorted_tables.insert(new_glyf_pos + 1, loca);
+            }
+
+            let indices = sorted_tables
+                .into_iter()
+                .map(|table| {
+                    let existing = tables.iter().position(|t| {
+                        t.tag == table.tag && t.offset == table.offset && t.length == table.length
+                    });
+                    existing.unwrap_or_else(|| {
+                        tables.push(table);
+                        tables.len() - 1
+                    })
+                })
+                .collect();
+            font_indices.push(indices);
+        }
+
+        (tables, font_indices)
+    }
+
+    fn extract_version(&self, tables: &[&SfntTable], font_indices: &[usize]) -> (u16, u16) {
+        TableRefs::new(tables, font_indices)
             .head
             .and_then(|head| {
-                let start = head.offset as usize;
+                let start = tables[head].offset as usize;
                 let data = self.data.get(start + 4..start + 8)?;
                 Some((
                     u16::from_be_bytes([data[0], data[1]]),
@@ -117,114 +441,130 @@ impl<'a> Encoder<'a> {
             .unwrap_or((0, 0))
     }
 
-    fn transform_glyf_if_needed(&self, table_refs: &TableRefs) -> Result<Option<Vec<u8>>, Error> {
+    /// Decide how each table is stored, applying the `glyf`/`loca` transform to every distinct
+    /// `glyf`/`loca` pair when enabled, and the `hmtx` transform to the `hmtx` table alongside it.
+    fn stored_tables<'t>(
+        &self,
+        tables: &[&'t SfntTable],
+        font_indices: &[Vec<usize>],
+    ) -> Result<Vec<StoredTable<'t>>, Error> {
+        let mut stored: Vec<_> = tables
+            .iter()
+            .map(|&table| StoredTable {
+                table,
+                transform_version: if table.tag.is_glyf() || table.tag.is_loca() { 3 } else { 0 },
+                transformed: None,
+            })
+            .collect();
+
         if !self.options.transform_glyf_loca {
-            return Ok(None);
+            return Ok(stored);
         }
 
-        if let (Some(glyf), Some(loca), Some(head), Some(maxp)) =
-            (table_refs.glyf, table_refs.loca, table_refs.head, table_refs.maxp)
-        {
-            let glyf_data = self.table_slice(glyf);
-            let loca_data = self.table_slice(loca);
-            let head_data = self.table_slice(head);
-            let maxp_data = self.table_slice(maxp);
+        for indices in font_indices {
+            let refs = TableRefs::new(tables, indices);
+            let (Some(glyf), Some(loca), Some(head), Some(maxp)) =
+                (refs.glyf, refs.loca, refs.head, refs.maxp)
+            else {
+                continue;
+            };
+            // A pair that was already transformed for an earlier font in the collection, or
+            // whose glyf and loca are not adjacent because only one of them is shared, is left
+            // as-is.
+            if loca != glyf + 1 || stored[glyf].transformed.is_some() {
+                continue;
+            }
 
             let transformed = GlyfContext {
-                glyf: glyf_data,
-                loca: loca_data,
-                head: head_data,
-                maxp: maxp_data,
+                glyf: self.table_slice(tables[glyf]),
+                loca: self.table_slice(tables[loca]),
+                head: self.table_slice(tables[head]),
+                maxp: self.table_slice(tables[maxp]),
+                hooks: self.hooks(),
             }
             .transform()?;
-            return Ok(Some(transformed));
+
+            stored[glyf].transform_version = 0;
+            stored[glyf].transformed = Some(transformed.glyf);
+            stored[loca].transform_version = 0;
+            stored[loca].transformed = Some(Vec::new());
+
+            let (Some(hhea), Some(hmtx)) = (refs.hhea, refs.hmtx) else {
+                continue;
+            };
+            // A decoder restores side bearings from the glyf table of the font it reconstructs
+            // hmtx for, so an hmtx shared by fonts with different glyf tables is left as-is.
+            let shares_glyf = font_indices
+                .iter()
+                .filter(|indices| indices.contains(&hmtx))
+                .all(|indices| indices.contains(&glyf));
+            if !self.options.transform_hmtx || !shares_glyf || stored[hmtx].transformed.is_some() {
+                continue;
+            }
+            let context = HmtxContext {
+                hhea: self.table_slice(tables[hhea]),
+                x_mins: &transformed.x_mins,
+            };
+            if let Some(transformed) = context.transform(self.table_slice(tables[hmtx])) {
+                stored[hmtx].transform_version = 1;
+                stored[hmtx].transformed = Some(transformed);
+            }
         }
 
-        Ok(None)
+        Ok(stored)
     }
 
-    fn build_directory_entries(
-        &self,
-        sorted_tables: &[&SfntTable],
-        transformed_glyf_len: Option<u32>,
-    ) -> Vec<TableDirectoryEntry> {
-        sorted_tables
+    fn build_directory_entries(&self, stored_tables: &[StoredTable]) -> Vec<TableDirectoryEntry> {
+        stored_tables
             .iter()
-            .map(|t| {
-                let is_glyf = t.tag.is_glyf();
-                let is_loca = t.tag.is_loca();
-                let (transform_version, transform_length) = match transformed_glyf_len {
-                    Some(len) => (0, is_glyf.then_some(len).or(is_loca.then_some(0))),
-                    None => (if is_glyf || is_loca { 3 } else { 0 }, None),
-                };
-
-                TableDirectoryEntry {
-                    tag: t.tag,
-                    orig_length: t.length,
-                    transform_version,
-                    transform_length,
-                }
+            .map(|t| TableDirectoryEntry {
+                tag: t.table.tag,
+                orig_length: t.table.length,
+                transform_version: t.transform_version,
+                transform_length: t.transformed.as_ref().map(|data| data.len() as u32),
             })
             .collect()
     }
 
-    fn encode_directory_entries(
+    /// Encode the table directory, followed by the collection directory for collections.
+    fn encode_directory(
         &self,
         entries: &[TableDirectoryEntry],
-    ) -> (Vec<InlineBytes<15>>, usize) {
-        let encoded: Vec<_> = entries.iter().map(InlineBytes::from).collect();
-        let size = encoded.iter().map(InlineBytes::len).sum();
-        (encoded, size)
-    }
-
-    fn build_uncompressed_data(
-        &self,
-        sorted_tables: &[&SfntTable],
-        transformed_glyf: Option<&[u8]>,
+        font_indices: &[Vec<usize>],
     ) -> Vec<u8> {
-        let total_len: usize = match transformed_glyf {
-            Some(tglyf) => sorted_tables
-                .iter()
-                .map(|table| {
-                    if table.tag.is_loca() {
-                        0
-                    } else if table.tag.is_glyf() {
-                        tglyf.len()
-                    } else {
-                        table.length as usize
-                    }
-                })
-                .sum(),
-            None => sorted_tables.iter().map(|table| table.length as usize).sum(),
-        };
+        let encoded: Vec<_> = entries.iter().map(InlineBytes::<15>::from).collect();
+        let mut directory = Vec::with_capacity(encoded.iter().map(InlineBytes::len).sum());
+        for entry in &encoded {
+            directory.extend_from_slice(entry.as_slice());
+        }
 
-        let mut data = Vec::with_capacity(total_len);
-        if let Some(tglyf) = transformed_glyf {
-            for table in sorted_tables {
-                if table.tag.is_loca() {
-                    continue;
-                }
-                if table.tag.is_glyf() {
-                    data.extend_from_slice(tglyf);
-                } else {
-                    data.extend_from_slice(self.table_slice(table));
+        if let FontFile::Collection { version, fonts } = &self.font {
+            directory.extend_from_slice(&version.to_be_bytes());
+            directory.extend_from_slice(encode_255_u_int16(fonts.len() as u16).as_slice());
+            for (sfnt, indices) in fonts.iter().zip(font_indices) {
+                directory.extend_from_slice(encode_255_u_int16(indices.len() as u16).as_slice());
+                directory.extend_from_slice(&sfnt.flavor.to_be_bytes());
+                for &index in indices {
+                    directory.extend_from_slice(encode_255_u_int16(index as u16).as_slice());
                 }
             }
-        } else {
-            for table in sorted_tables {
-                data.extend_from_slice(self.table_slice(table));
-            }
         }
-        data
+
+        directory
     }
 
-    fn compress(&self, uncompressed_data: &[u8]) -> Result<Vec<u8>, Error> {
-        let params = BrotliEncoderParams {
-            quality: self.options.quality.into(),
-            mode: BrotliEncoderMode::BROTLI_MODE_FONT,
-            size_hint: uncompressed_data.len(),
-            ..Default::default()
-        };
+    fn stored_data<'s>(&'s self, table: &'s StoredTable) -> &'s [u8] {
+        table
+            .transformed
+            .as_deref()
+            .unwrap_or_else(|| self.table_slice(table.table))
+    }
+
+    /// Brotli-compress the stored data of every table as a single stream.
+    fn compress(&self, stored_tables: &[StoredTable]) -> Result<Vec<u8>, Error> {
+        let slices: Vec<_> = stored_tables.iter().map(|t| self.stored_data(t)).collect();
+        let total_len = slices.iter().map(|data| data.len()).sum();
+        let params = self.brotli_params(total_len);
 
         // WASM cannot spawn OS threads (`std::thread::spawn` panics on
         // `wasm32-unknown-unknown`), so silently force single-threaded there
@@ -235,12 +575,30 @@ impl<'a> Encoder<'a> {
             self.options.threads.map_or(1, NonZeroUsize::get)
         };
         if num_threads <= 1 {
-            let mut compressed_data = Vec::with_capacity(uncompressed_data.len());
-            BrotliCompress(&mut &uncompressed_data[..], &mut compressed_data, &params)
-                .map_err(|e| Error::Compression(e.to_string()))?;
-            return Ok(compressed_data);
+            let consumed = Cell::new(0);
+            let mut input = ChainedSlices {
+                slices: slices.iter(),
+                current: &[],
+                consumed: &consumed,
+            };
+            let mut output = HookedOutput {
+                data: Vec::with_capacity(total_len),
+                hooks: self.hooks(),
+                consumed: &consumed,
+                reported: 0,
+                total: total_len as u64,
+                error: None,
+            };
+            return match BrotliCompress(&mut input, &mut output, &params) {
+                Ok(_) => Ok(output.data),
+                Err(e) => Err(output.error.unwrap_or_else(|| Error::Compression(e.to_string()))),
+            };
         }
 
+        // The multi-threaded encoder splits its input into slices itself, so it needs the
+        // concatenated table data.
+        let uncompressed_data = slices.concat();
+
         // Multi-threaded path. Worst-case output bound: input length + per-thread overhead.
         // (Brotli rarely expands input; the slack covers per-metablock headers.)
         let mut output = vec![0u8; uncompressed_data.len() + 1024 * num_threads + 1024];
@@ -250,63 +608,199 @@ impl<'a> Encoder<'a> {
         let mut spawner = MultiThreadedSpawner::default();
         let written = CompressMultiSlice(
             &params,
-            uncompressed_data,
+            &uncompressed_data,
             &mut output[..],
             &mut allocs[..],
             &mut spawner,
         )
         .map_err(|e| Error::Compression(format!("{e:?}")))?;
         output.truncate(written);
+        let total = uncompressed_data.len() as u64;
+        self.hooks().report(Progress::Compressed { done: total, total })?;
         Ok(output)
     }
 
-    fn build_output(
+    fn compress_metadata(&self) -> Result<Option<CompressedMetadata>, Error> {
+        let Some(xml) = &self.options.metadata else {
+            return Ok(None);
+        };
+        let params = BrotliEncoderParams {
+            quality: self.options.quality.into(),
+            mode: BrotliEncoderMode::BROTLI_MODE_TEXT,
+            size_hint: xml.len(),
+            ..Default::default()
+        };
+        let mut data = Vec::with_capacity(xml.len());
+        BrotliCompress(&mut xml.as_bytes(), &mut data, &params)
+            .map_err(|e| Error::Compression(e.to_string()))?;
+        Ok(Some(CompressedMetadata { data, orig_length: xml.len() as u32 }))
+    }
+
+    /// The `totalSfntSize` header field: the size of the uncompressed font file, with every
+    /// table padded to a 4-byte boundary and shared collection tables counted once.
+    fn total_sfnt_size(&self, tables: &[&SfntTable]) -> u32 {
+        let header_size = match &self.font {
+            FontFile::Single(_) => 0,
+            FontFile::Collection { version, fonts } => {
+                12 + 4 * fonts.len() as u32 + if *version >= 0x00020000 { 12 } else { 0 }
+            }
+        };
+        let directories_size: u32 = self
+            .font
+            .fonts()
+            .iter()
+            .map(|sfnt| 12 + 16 * sfnt.tables.len() as u32)
+            .sum();
+
+        header_size + directories_size + tables.iter().map(|t| align4(t.length)).sum::<u32>()
+    }
+
+    /// Run the encoding pipeline, producing everything but the header.
+    fn encode_font(&self) -> Result<EncodedFont, Error> {
+        self.check_cancelled()?;
+        let (tables, font_indices) = self.directory_order();
+        let stored_tables = self.stored_tables(&tables, &font_indices)?;
+        self.check_cancelled()?;
+        self.assemble(&tables, &font_indices, &stored_tables)
+    }
+
+    /// Compress the stored tables and build the table directory describing them.
+    fn assemble(
         &self,
-        sorted_tables: &[&SfntTable],
-        encoded_directory: &[InlineBytes<15>],
-        directory_size: usize,
-        compressed_data: &[u8],
-        major_version: u16,
-        minor_version: u16,
-    ) -> Vec<u8> {
-        fn align4(value: u32) -> u32 {
-            (value + 3) & !3
-        }
+        tables: &[&SfntTable],
+        font_indices: &[Vec<usize>],
+        stored_tables: &[StoredTable],
+    ) -> Result<EncodedFont, Error> {
+        let directory_entries = self.build_directory_entries(stored_tables);
+
+        Ok(EncodedFont {
+            num_tables: tables.len() as u16,
+            total_sfnt_size: self.total_sfnt_size(tables),
+            version: self.extract_version(tables, &font_indices[0]),
+            directory: self.encode_directory(&directory_entries, font_indices),
+            compressed_data: self.compress(stored_tables)?,
+            metadata: self.compress_metadata()?,
+        })
+    }
 
-        let total_sfnt_size = 12
-            + 16 * self.sfnt.tables.len() as u32
-            + sorted_tables.iter().map(|t| align4(t.length)).sum::<u32>();
+    /// Report the sizes of an encoded font, compressing each table on its own to estimate its
+    /// share of the Brotli stream.
+    fn report(
+        &self,
+        stored_tables: &[StoredTable],
+        font: &EncodedFont,
+    ) -> Result<EncodeReport, Error> {
+        let brotli_lengths = stored_tables
+            .iter()
+            .map(|table| {
+                let data = self.stored_data(table);
+                let params = self.brotli_params(data.len());
+                let mut compressed = Vec::new();
+                BrotliCompress(&mut &data[..], &mut compressed, &params)
+                    .map_err(|e| Error::Compression(e.to_string()))?;
+                Ok(compressed.len() as u64)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let brotli_total: u64 = brotli_lengths.iter().sum();
+        let compressed_total = font.compressed_data.len() as u64;
 
-        let unpadded_length = 48 + directory_size as u32 + compressed_data.len() as u32;
-        // WOFF2 file must be padded to 4-byte boundary
-        let total_length = align4(unpadded_length);
+        let tables = stored_tables
+            .iter()
+            .zip(&brotli_lengths)
+            .map(|(table, &brotli_length)| {
+                let glyf_streams = match &table.transformed {
+                    Some(data) if table.table.tag.is_glyf() => Some(glyf_streams(data)?),
+                    _ => None,
+                };
+                Ok(TableReport {
+                    tag: table.table.tag,
+                    orig_length: table.table.length,
+                    transform_length: table.transformed.as_ref().map(|data| data.len() as u32),
+                    brotli_length: brotli_length as u32,
+                    compressed_share: (brotli_length * compressed_total / brotli_total.max(1))
+                        as u32,
+                    glyf_streams,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let header = self.build_header(font);
+        Ok(EncodeReport {
+            length: header.length,
+            total_sfnt_size: header.total_sfnt_size,
+            directory_length: 48 + font.directory.len() as u32,
+            total_compressed_size: header.total_compressed_size,
+            metadata_length: header.meta_length,
+            private_data_length: header.priv_length,
+            tables,
+        })
+    }
 
-        let header = Woff2Header {
+    fn build_header(&self, font: &EncodedFont) -> Woff2Header {
+        let mut unpadded_length =
+            48 + font.directory.len() as u32 + font.compressed_data.len() as u32;
+        // The metadata block must begin on a 4-byte boundary
+        let metadata = font.metadata.as_ref();
+        let meta_offset = metadata.map_or(0, |_| align4(unpadded_length));
+        if let Some(metadata) = metadata {
+            unpadded_length = meta_offset + metadata.data.len() as u32;
+        }
+        // The private data block must begin on a 4-byte boundary and be the last block
+        let private_data = self.options.private_data.as_deref();
+        let priv_offset = private_data.map_or(0, |_| align4(unpadded_length));
+        if let Some(private_data) = private_data {
+            unpadded_length = priv_offset + private_data.len() as u32;
+        }
+
+        let flavor = match &self.font {
+            FontFile::Single(sfnt) => sfnt.flavor,
+            FontFile::Collection { .. } => COLLECTION_FLAVOR,
+        };
+        let (major_version, minor_version) = font.version;
+
+        Woff2Header {
             signature: WOFF2_SIGNATURE,
-            flavor: self.sfnt.flavor,
-            length: total_length,
-            num_tables: self.sfnt.tables.len() as u16,
+            flavor,
+            // WOFF2 file must be padded to 4-byte boundary
+            length: align4(unpadded_length),
+            num_tables: font.num_tables,
             reserved: 0,
-            total_sfnt_size,
-            total_compressed_size: compressed_data.len() as u32,
+            total_sfnt_size: font.total_sfnt_size,
+            total_compressed_size: font.compressed_data.len() as u32,
             major_version,
             minor_version,
-            meta_offset: 0,
-            meta_length: 0,
-            meta_orig_length: 0,
-            priv_offset: 0,
-            priv_length: 0,
-        };
+            meta_offset,
+            meta_length: metadata.map_or(0, |m| m.data.len() as u32),
+            meta_orig_length: metadata.map_or(0, |m| m.orig_length),
+            priv_offset,
+            priv_length: private_data.map_or(0, |data| data.len() as u32),
+        }
+    }
 
-        let mut result = Vec::with_capacity(total_length as usize);
-        result.extend_from_slice(&<[u8; 48]>::from(&header));
-        for entry in encoded_directory {
-            result.extend_from_slice(entry.as_slice());
+    /// Write the WOFF2 file piece by piece, returning the number of bytes written.
+    fn write_to<W: Write>(&self, font: &EncodedFont, writer: &mut W) -> Result<usize, Error> {
+        let header = self.build_header(font);
+
+        let mut position = 0;
+        let mut write_at = |offset: usize, data: &[u8]| -> io::Result<()> {
+            // Zero padding up to the 4-byte aligned start of the block
+            writer.write_all(&[0u8; 3][..offset - position])?;
+            writer.write_all(data)?;
+            position = offset + data.len();
+            Ok(())
+        };
+        write_at(0, &<[u8; 48]>::from(&header))?;
+        write_at(48, &font.directory)?;
+        write_at(48 + font.directory.len(), &font.compressed_data)?;
+        if let Some(metadata) = &font.metadata {
+            write_at(header.meta_offset as usize, &metadata.data)?;
         }
-        result.extend_from_slice(compressed_data);
-        // Add padding to reach 4-byte alignment
-        result.resize(total_length as usize, 0u8);
-        result
+        if let Some(private_data) = &self.options.private_data {
+            write_at(header.priv_offset as usize, private_data)?;
+        }
+        write_at(header.length as usize, &[])?;
+
+        Ok(header.length as usize)
     }
 }
 
@@ -314,50 +808,22 @@ impl TryFrom<Encoder<'_>> for Vec<u8> {
     type Error = Error;
 
     fn try_from(encoder: Encoder<'_>) -> Result<Self, Self::Error> {
-        let mut sorted_tables: Vec<_> = encoder.sfnt.tables.iter().collect();
-        sorted_tables.sort_by_key(|t| t.tag);
-
-        // WOFF2 spec requires loca to immediately follow glyf in the table directory
-        if let Some(glyf_pos) = sorted_tables.iter().position(|t| t.tag.is_glyf())
-            && let Some(loca_pos) = sorted_tables.iter().position(|t| t.tag.is_loca())
-            && loca_pos != glyf_pos + 1
-        {
-            let loca = sorted_tables.remove(loca_pos);
-            let new_glyf_pos = sorted_tables.iter().position(|t| t.tag.is_glyf()).unwrap();
-            sorted_tables.insert(new_glyf_pos + 1, loca);
-        }
-
-        let table_refs = TableRefs::from_sorted(&sorted_tables);
-        let (major_version, minor_version) = encoder.extract_version(&table_refs);
-        let transformed_glyf = encoder.transform_glyf_if_needed(&table_refs)?;
-        let transformed_glyf_len = transformed_glyf.as_ref().map(|v| v.len() as u32);
-
-        let directory_entries =
-            encoder.build_directory_entries(&sorted_tables, transformed_glyf_len);
-        let (encoded_directory, directory_size) =
-            encoder.encode_directory_entries(&directory_entries);
-        let uncompressed_data =
-            encoder.build_uncompressed_data(&sorted_tables, transformed_glyf.as_deref());
-        let compressed_data = encoder.compress(&uncompressed_data)?;
-
-        let result = encoder.build_output(
-            &sorted_tables,
-            &encoded_directory,
-            directory_size,
-            &compressed_data,
-            major_version,
-            minor_version,
-        );
-
+        let font = encoder.encode_font()?;
+        let mut result = Vec::with_capacity(encoder.build_header(&font).length as usize);
+        encoder.write_to(&font, &mut result)?;
         Ok(result)
     }
 }
 
-/// Encode a TTF font as WOFF2 with the `glyf`/`loca` transformation enabled.
+/// Encode a TTF or OTF font as WOFF2 with the `glyf`/`loca` transformation enabled.
 ///
 /// This is the recommended entry point and produces the smallest output for
-/// TrueType fonts. Only TrueType (TTF) input is supported; OpenType/CFF fonts
-/// return [`Error::UnsupportedFormat`].
+/// TrueType fonts. CFF-flavored OpenType (`OTTO`) fonts are accepted too; their
+/// `CFF `/`CFF2` tables are stored untransformed as the WOFF2 spec requires.
+/// TrueType collections (`.ttc`) are encoded in the WOFF2 collection format, with
+/// tables shared between fonts stored once and each distinct `glyf`/`loca` pair
+/// transformed. WOFF 1.0 files are unpacked and transcoded, keeping their metadata and private
+/// data blocks. Any other SFNT flavor returns [`Error::UnsupportedFormat`].
 ///
 /// `quality` selects the Brotli compression quality (0-11); use
 /// [`BrotliQuality::default`] (11) for smallest output, or a lower value for
@@ -365,14 +831,14 @@ impl TryFrom<Encoder<'_>> for Vec<u8> {
 ///
 /// # Errors
 ///
-/// Returns an [`Error`] if the input is not a valid TTF font, a table extends
+/// Returns an [`Error`] if the input is not a valid TTF/OTF font, a table extends
 /// beyond the input bounds, glyph data is malformed, or Brotli compression fails.
 pub fn encode(ttf_data: &[u8], quality: BrotliQuality) -> Result<Vec<u8>, Error> {
     let options = EncodeOptions { quality, ..EncodeOptions::default() };
     Encoder::new(ttf_data, options)?.try_into()
 }
 
-/// Encode a TTF font as WOFF2 with full control over [`EncodeOptions`].
+/// Encode a TTF or OTF font as WOFF2 with full control over [`EncodeOptions`].
 ///
 /// Use this when you need to override defaults — e.g. to enable multi-threaded
 /// Brotli compression via [`EncodeOptions::threads`], or to disable the
@@ -383,7 +849,56 @@ pub fn encode_with_options(ttf_data: &[u8], options: EncodeOptions) -> Result<Ve
     Encoder::new(ttf_data, options)?.try_into()
 }
 
-/// Encode a TTF font as WOFF2 without applying the `glyf`/`loca` transformation.
+/// Encode a TTF or OTF font as WOFF2 and write it to `writer`.
+///
+/// Unlike [`encode_with_options`], the WOFF2 file is never assembled in memory: the header and
+/// table directory are written first, followed by the compressed font data and the metadata and
+/// private data blocks, if any. With single-threaded compression the tables are also fed to
+/// Brotli one by one rather than concatenated first. The compressed font data itself is still
+/// buffered, since the header records its size ahead of it.
+///
+/// Returns the number of bytes written. The writer is not flushed.
+///
+/// See [`encode`] for argument semantics.
+///
+/// # Errors
+///
+/// Returns an [`Error`] for the same reasons as [`encode`], or [`Error::Io`] if writing fails.
+/// Nothing is written unless encoding succeeds, but a failed write may leave a partial file
+/// behind.
+pub fn encode_to<W: Write>(
+    ttf_data: &[u8],
+    options: EncodeOptions,
+    mut writer: W,
+) -> Result<usize, Error> {
+    let encoder = Encoder::new(ttf_data, options)?;
+    let font = encoder.encode_font()?;
+    encoder.write_to(&font, &mut writer)
+}
+
+/// Encode a TTF or OTF font as WOFF2, along with a report of where its bytes go.
+///
+/// Besides the regular encode, every table is Brotli-compressed on its own to estimate its share
+/// of the compressed font data, which roughly doubles the encoding time; see
+/// [`TableReport::compressed_share`].
+///
+/// See [`encode`] for argument and error semantics.
+pub fn encode_with_report(
+    ttf_data: &[u8],
+    options: EncodeOptions,
+) -> Result<(Vec<u8>, EncodeReport), Error> {
+    let encoder = Encoder::new(ttf_data, options)?;
+    let (tables, font_indices) = encoder.directory_order();
+    let stored_tables = encoder.stored_tables(&tables, &font_indices)?;
+    let font = encoder.assemble(&tables, &font_indices, &stored_tables)?;
+    let report = encoder.report(&stored_tables, &font)?;
+
+    let mut woff2_data = Vec::with_capacity(report.length as usize);
+    encoder.write_to(&font, &mut woff2_data)?;
+    Ok((woff2_data, report))
+}
+
+/// Encode a TTF or OTF font as WOFF2 without applying the `glyf`/`loca` transformation.
 ///
 /// Tables are stored as-is before Brotli compression. Output is typically larger
 /// than [`encode`], but encoding skips the transformation step. Useful for
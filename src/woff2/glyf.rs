@@ -0,0 +1,68 @@
+use crate::{Error, Error::DataTooShort};
+
+pub(super) fn read_u16(data: &[u8], pos: usize, context: &'static str) -> Result<u16, Error> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(DataTooShort { context })
+}
+
+pub(super) fn read_u32(data: &[u8], pos: usize, context: &'static str) -> Result<u32, Error> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(DataTooShort { context })
+}
+
+/// Whether `loca` uses the long format, according to `head.indexToLocFormat`.
+pub(super) fn is_long_loca(head: &[u8]) -> Result<bool, Error> {
+    match read_u16(head, 50, "head table")? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(Error::InvalidGlyph("invalid indexToLocFormat")),
+    }
+}
+
+/// The data of every glyph in `glyf`, indexed by glyph ID.
+pub(super) fn glyph_slices<'a>(
+    glyf: &'a [u8],
+    loca: &[u8],
+    long_loca: bool,
+    num_glyphs: usize,
+) -> Result<Vec<&'a [u8]>, Error> {
+    let offset = |index: usize| -> Result<usize, Error> {
+        Ok(if long_loca {
+            read_u32(loca, 4 * index, "loca table")? as usize
+        } else {
+            2 * read_u16(loca, 2 * index, "loca table")? as usize
+        })
+    };
+    (0..num_glyphs)
+        .map(|glyph_id| {
+            let (start, end) = (offset(glyph_id)?, offset(glyph_id + 1)?);
+            glyf.get(start..end)
+                .ok_or(Error::InvalidGlyph("loca offsets out of bounds"))
+        })
+        .collect()
+}
+
+/// Concatenate glyphs into new `glyf` and `loca` tables, returning them along with whether
+/// `loca` uses the long format.
+///
+/// Each glyph is padded to an even length, so the short format is used whenever the glyphs fit.
+pub(super) fn build_glyf_loca<G: AsRef<[u8]>>(glyphs: &[G]) -> (Vec<u8>, Vec<u8>, bool) {
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(glyphs.len() + 1);
+    for glyph in glyphs {
+        offsets.push(glyf.len());
+        glyf.extend_from_slice(glyph.as_ref());
+        // Short loca offsets are stored halved
+        glyf.resize((glyf.len() + 1) & !1, 0);
+    }
+    offsets.push(glyf.len());
+    let long_loca = glyf.len() > 2 * usize::from(u16::MAX);
+    let loca = if long_loca {
+        offsets.iter().flat_map(|&o| (o as u32).to_be_bytes()).collect()
+    } else {
+        offsets.iter().flat_map(|&o| ((o / 2) as u16).to_be_bytes()).collect()
+    };
+    (glyf, loca, long_loca)
+}
@@ -1,3 +1,5 @@
+use crate::{Error, Error::DataTooShort};
+
 pub(crate) const WOFF2_SIGNATURE: u32 = 0x774F4632; // 'wOF2'
 
 pub(crate) struct Woff2Header {
@@ -38,6 +40,45 @@ impl From<&Woff2Header> for [u8; 48] {
     }
 }
 
+impl TryFrom<&[u8]> for Woff2Header {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let bytes = data.get(..48).ok_or(DataTooShort { context: "WOFF2 header" })?;
+        let u16_at = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+        let header = Self {
+            signature: u32_at(0),
+            flavor: u32_at(4),
+            length: u32_at(8),
+            num_tables: u16_at(12),
+            reserved: u16_at(14),
+            total_sfnt_size: u32_at(16),
+            total_compressed_size: u32_at(20),
+            major_version: u16_at(24),
+            minor_version: u16_at(26),
+            meta_offset: u32_at(28),
+            meta_length: u32_at(32),
+            meta_orig_length: u32_at(36),
+            priv_offset: u32_at(40),
+            priv_length: u32_at(44),
+        };
+
+        if header.signature != WOFF2_SIGNATURE {
+            return Err(Error::InvalidWoff2("bad signature"));
+        }
+        if header.length as usize > data.len() {
+            return Err(DataTooShort { context: "WOFF2 file" });
+        }
+        if header.num_tables == 0 {
+            return Err(Error::InvalidWoff2("no tables"));
+        }
+        Ok(header)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +125,30 @@ mod tests {
         let bytes: [u8; 48] = (&header).into();
         assert_eq!(&bytes[0..4], b"wOF2");
     }
+
+    #[test]
+    fn header_roundtrips_through_bytes() {
+        let header = Woff2Header {
+            signature: WOFF2_SIGNATURE,
+            flavor: 0x00010000,
+            length: 48,
+            num_tables: 3,
+            reserved: 0,
+            total_sfnt_size: 1234,
+            total_compressed_size: 567,
+            major_version: 2,
+            minor_version: 5,
+            meta_offset: 0,
+            meta_length: 0,
+            meta_orig_length: 0,
+            priv_offset: 0,
+            priv_length: 0,
+        };
+        let bytes: [u8; 48] = (&header).into();
+        let parsed = Woff2Header::try_from(&bytes[..]).unwrap();
+        assert_eq!(parsed.num_tables, 3);
+        assert_eq!(parsed.total_sfnt_size, 1234);
+        assert_eq!(parsed.total_compressed_size, 567);
+        assert_eq!((parsed.major_version, parsed.minor_version), (2, 5));
+    }
 }
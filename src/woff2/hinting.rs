@@ -0,0 +1,239 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use super::{
+    glyf::{build_glyf_loca, glyph_slices, is_long_loca, read_u16},
+    sfnt::{FontFile, write_collection, write_sfnt},
+    tag::Tag,
+    transform::walk_components,
+};
+use crate::Error;
+
+/// Tables that hold TrueType hinting programs and data, or metrics computed by running them.
+///
+/// `cvar` varies the `cvt ` values, so it goes along with them.
+const HINTING_TABLES: [&[u8; 4]; 7] =
+    [b"cvar", b"cvt ", b"fpgm", b"hdmx", b"LTSH", b"prep", b"VDMX"];
+
+/// `head.flags` bits describing the effect of instructions: bit 2, "instructions may depend on
+/// point size", and bit 4, "instructions may alter advance width".
+const HEAD_INSTRUCTION_FLAGS: u16 = (1 << 2) | (1 << 4);
+
+/// Remove the TrueType hinting from a font or collection, returning the rebuilt SFNT.
+///
+/// The instructions of every glyph are emptied, the tables in [`HINTING_TABLES`] are dropped,
+/// the instruction-related `maxp` limits are reset and the `head` flags describing the effect of
+/// instructions are cleared. Tables shared between the fonts of a collection stay shared. CFF
+/// hints live in the charstrings and are left as they are.
+pub(super) fn strip_hinting(data: &[u8], font: &FontFile) -> Result<Vec<u8>, Error> {
+    let mut tables: Vec<(Tag, Cow<[u8]>)> = Vec::new();
+    let mut fonts = Vec::with_capacity(font.fonts().len());
+    // Index in `tables` of each table by its tag, offset and length in the input, to keep shared
+    // tables shared. The offset alone is not enough: an empty table may start where the next
+    // table does.
+    let mut indices = HashMap::new();
+    for sfnt in font.fonts() {
+        let mut font_indices = Vec::with_capacity(sfnt.tables.len());
+        for table in &sfnt.tables {
+            if HINTING_TABLES.contains(&&table.tag.0) {
+                continue;
+            }
+            let key = (table.tag, table.offset, table.length);
+            let index = *indices.entry(key).or_insert_with(|| {
+                let slice = &data[table.offset as usize..][..table.length as usize];
+                tables.push((table.tag, Cow::Borrowed(slice)));
+                tables.len() - 1
+            });
+            font_indices.push(index);
+        }
+        fonts.push((sfnt.flavor, font_indices));
+    }
+
+    let mut stripped_glyf = Vec::new();
+    for (_, font_indices) in &fonts {
+        let find = |tag: Tag| font_indices.iter().copied().find(|&i| tables[i].0 == tag);
+        let (Some(glyf), Some(loca), Some(head), Some(maxp)) =
+            (find(Tag::GLYF), find(Tag::LOCA), find(Tag::HEAD), find(Tag::MAXP))
+        else {
+            continue;
+        };
+        if stripped_glyf.contains(&glyf) {
+            continue;
+        }
+        stripped_glyf.push(glyf);
+
+        let num_glyphs = read_u16(&tables[maxp].1, 4, "maxp table")? as usize;
+        let glyphs = glyph_slices(
+            &tables[glyf].1,
+            &tables[loca].1,
+            is_long_loca(&tables[head].1)?,
+            num_glyphs,
+        )?;
+        let glyphs = glyphs.into_iter().map(strip_glyph).collect::<Result<Vec<_>, _>>()?;
+        let (new_glyf, new_loca, long_loca) = build_glyf_loca(&glyphs);
+        tables[glyf].1 = Cow::Owned(new_glyf);
+        tables[loca].1 = Cow::Owned(new_loca);
+        tables[head].1.to_mut()[50..52].copy_from_slice(&u16::from(long_loca).to_be_bytes());
+    }
+
+    for (tag, table) in &mut tables {
+        if tag.is_head() {
+            let flags = read_u16(table, 16, "head table")?;
+            let flags = flags & !HEAD_INSTRUCTION_FLAGS;
+            table.to_mut()[16..18].copy_from_slice(&flags.to_be_bytes());
+        } else if tag.is_maxp() && table.len() >= 32 {
+            // maxZones through maxSizeOfInstructions of a version 1.0 maxp table; a single zone
+            // means no twilight zone
+            let maxp = table.to_mut();
+            maxp[14..16].copy_from_slice(&1u16.to_be_bytes());
+            maxp[16..28].fill(0);
+        }
+    }
+
+    let tables: Vec<_> = tables.iter().map(|(tag, data)| (*tag, &data[..])).collect();
+    Ok(match font {
+        FontFile::Single(sfnt) => write_sfnt(sfnt.flavor, &tables),
+        FontFile::Collection { version, .. } => write_collection(*version, &fonts, &tables),
+    })
+}
+
+/// A glyph with its instructions removed.
+fn strip_glyph(glyph: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    if glyph.len() < 10 {
+        return Ok(Cow::Borrowed(glyph));
+    }
+    let num_contours = i16::from_be_bytes([glyph[0], glyph[1]]);
+    if num_contours >= 0 {
+        let length_pos = 10 + 2 * num_contours as usize;
+        let instruction_length = read_u16(glyph, length_pos, "simple glyph")? as usize;
+        if instruction_length == 0 {
+            return Ok(Cow::Borrowed(glyph));
+        }
+        let rest = glyph
+            .get(length_pos + 2 + instruction_length..)
+            .ok_or(Error::InvalidGlyph("glyph instructions exceed bounds"))?;
+        let mut stripped = Vec::with_capacity(length_pos + 2 + rest.len());
+        stripped.extend_from_slice(&glyph[..length_pos]);
+        stripped.extend_from_slice(&0u16.to_be_bytes());
+        stripped.extend_from_slice(rest);
+        Ok(Cow::Owned(stripped))
+    } else {
+        let mut flag_positions = Vec::new();
+        let (end, have_instructions) =
+            walk_components(glyph, 10, |pos| flag_positions.push(pos - 2))?;
+        if !have_instructions {
+            return Ok(Cow::Borrowed(glyph));
+        }
+        // Drop the instructions after the last component and the flags announcing them
+        let mut stripped = glyph[..end].to_vec();
+        for pos in flag_positions {
+            stripped[pos] &= !0x01; // WE_HAVE_INSTRUCTIONS (0x0100) in the high byte
+        }
+        Ok(Cow::Owned(stripped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read, path::PathBuf};
+
+    use super::*;
+
+    fn read_fixture(name: &str) -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        read(path).expect("Failed to read test font")
+    }
+
+    #[test]
+    fn test_strip_simple_glyph() {
+        // One contour with a single on-curve point and two bytes of instructions
+        let glyph = [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0xB0, 0x00, 0x31];
+        let stripped = strip_glyph(&glyph).unwrap();
+        assert_eq!(&stripped[..], [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x31]);
+
+        let unhinted = strip_glyph(&stripped).unwrap();
+        assert!(matches!(unhinted, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_strip_composite_glyph() {
+        let mut glyph = vec![0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0];
+        // MORE_COMPONENTS | WE_HAVE_INSTRUCTIONS, glyph 1, byte offsets
+        glyph.extend_from_slice(&[0x01, 0x20, 0, 1, 0, 0]);
+        // WE_HAVE_INSTRUCTIONS, glyph 2, byte offsets
+        glyph.extend_from_slice(&[0x01, 0x00, 0, 2, 5, 5]);
+        glyph.extend_from_slice(&[0, 1, 0xB0]);
+
+        let stripped = strip_glyph(&glyph).unwrap();
+        assert_eq!(stripped.len(), glyph.len() - 3);
+        assert_eq!(&stripped[10..12], [0x00, 0x20]);
+        assert_eq!(&stripped[16..18], [0x00, 0x00]);
+        assert_eq!(&stripped[12..16], &glyph[12..16]);
+        assert_eq!(&stripped[18..], &glyph[18..22]);
+    }
+
+    #[test]
+    fn test_strip_hinting_fixture() {
+        let data = read_fixture("WarpnineSans-Regular.ttf");
+        let font = FontFile::try_from(&data[..]).unwrap();
+        let stripped = strip_hinting(&data, &font).unwrap();
+        let stripped_font = FontFile::try_from(&stripped[..]).unwrap();
+        stripped_font.verify_checksums(&stripped).unwrap();
+
+        let tables = &stripped_font.fonts()[0].tables;
+        assert!(tables.iter().all(|t| !HINTING_TABLES.contains(&&t.tag.0)));
+        assert_eq!(tables.len(), font.fonts()[0].tables.len() - 1, "only prep is dropped");
+
+        let table = |tag: Tag| {
+            let table = tables.iter().find(|t| t.tag == tag).unwrap();
+            &stripped[table.offset as usize..][..table.length as usize]
+        };
+        let maxp = table(Tag::MAXP);
+        assert_eq!(&maxp[14..28], [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let num_glyphs = read_u16(maxp, 4, "maxp").unwrap() as usize;
+        let head = table(Tag::HEAD);
+        let glyphs = glyph_slices(
+            table(Tag::GLYF),
+            table(Tag::LOCA),
+            is_long_loca(head).unwrap(),
+            num_glyphs,
+        )
+        .unwrap();
+        for glyph in glyphs {
+            assert!(matches!(strip_glyph(glyph).unwrap(), Cow::Borrowed(_)));
+        }
+    }
+
+    #[test]
+    fn test_strip_hinting_keeps_empty_table() {
+        let data = read_fixture("WarpnineSans-Regular.ttf");
+        let font = FontFile::try_from(&data[..]).unwrap();
+        let mut tables: Vec<_> = font.fonts()[0]
+            .tables
+            .iter()
+            .map(|t| (t.tag, &data[t.offset as usize..][..t.length as usize]))
+            .collect();
+        // An empty table sorted right before `OS/2`, written at the same offset
+        tables.insert(0, (Tag::from(*b"OS/1"), &[][..]));
+        tables.sort_by_key(|(tag, _)| *tag);
+        let with_empty = write_sfnt(font.fonts()[0].flavor, &tables);
+        let with_empty_font = FontFile::try_from(&with_empty[..]).unwrap();
+        let input_tables = &with_empty_font.fonts()[0].tables;
+        let empty = input_tables.iter().position(|t| t.length == 0).unwrap();
+        assert_eq!(input_tables[empty].offset, input_tables[empty + 1].offset);
+
+        let stripped = strip_hinting(&with_empty, &with_empty_font).unwrap();
+        let stripped_font = FontFile::try_from(&stripped[..]).unwrap();
+        let tags: Vec<_> = stripped_font.fonts()[0].tables.iter().map(|t| t.tag).collect();
+        let expected: Vec<_> = input_tables
+            .iter()
+            .map(|t| t.tag)
+            .filter(|tag| !HINTING_TABLES.contains(&&tag.0))
+            .collect();
+        assert_eq!(tags, expected);
+        let next = &stripped_font.fonts()[0].tables[empty + 1];
+        assert_eq!(next.tag, input_tables[empty + 1].tag);
+        assert_eq!(next.length, input_tables[empty + 1].length);
+    }
+}
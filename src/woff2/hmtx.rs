@@ -0,0 +1,191 @@
+use std::io::Cursor;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::Error;
+
+/// Set when the `lsb` of every `hMetrics` record is omitted.
+const PROPORTIONAL_LSB_ABSENT: u8 = 0x01;
+/// Set when the `leftSideBearing` array following `hMetrics` is omitted.
+const MONOSPACED_LSB_ABSENT: u8 = 0x02;
+
+/// The WOFF2 `hmtx` transform (version 1) and its inverse.
+///
+/// Left side bearings equal to the glyph's `xMin` are dropped, since a decoder can restore them
+/// from the reconstructed `glyf` table.
+pub(super) struct HmtxContext<'a> {
+    pub hhea: &'a [u8],
+    /// `xMin` of every glyph, 0 for empty glyphs
+    pub x_mins: &'a [i16],
+}
+
+impl HmtxContext<'_> {
+    /// `hhea.numberOfHMetrics`, if it is within `1..=numGlyphs`.
+    fn num_h_metrics(&self) -> Option<usize> {
+        let bytes = self.hhea.get(34..36)?;
+        let num_h_metrics = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        (1..=self.x_mins.len())
+            .contains(&num_h_metrics)
+            .then_some(num_h_metrics)
+    }
+
+    /// Transform `hmtx`, or return `None` when no side bearings can be dropped or the table does
+    /// not have the size `hhea` and the glyph count imply.
+    pub(super) fn transform(&self, hmtx: &[u8]) -> Option<Vec<u8>> {
+        let num_glyphs = self.x_mins.len();
+        let num_h_metrics = self.num_h_metrics()?;
+        if hmtx.len() != 4 * num_h_metrics + 2 * (num_glyphs - num_h_metrics) {
+            return None;
+        }
+
+        let (h_metrics, left_side_bearings) = hmtx.split_at(4 * num_h_metrics);
+        let proportional_lsb_absent = h_metrics
+            .chunks_exact(4)
+            .zip(self.x_mins)
+            .all(|(metric, &x_min)| i16::from_be_bytes([metric[2], metric[3]]) == x_min);
+        let monospaced_lsb_absent = left_side_bearings
+            .chunks_exact(2)
+            .zip(&self.x_mins[num_h_metrics..])
+            .all(|(lsb, &x_min)| i16::from_be_bytes([lsb[0], lsb[1]]) == x_min);
+        if !proportional_lsb_absent && !monospaced_lsb_absent {
+            return None;
+        }
+
+        let mut flags = 0;
+        let mut output = Vec::with_capacity(1 + hmtx.len());
+        output.push(0);
+        for metric in h_metrics.chunks_exact(4) {
+            output.extend_from_slice(&metric[..2]);
+        }
+        if proportional_lsb_absent {
+            flags |= PROPORTIONAL_LSB_ABSENT;
+        } else {
+            for metric in h_metrics.chunks_exact(4) {
+                output.extend_from_slice(&metric[2..]);
+            }
+        }
+        if monospaced_lsb_absent {
+            flags |= MONOSPACED_LSB_ABSENT;
+        } else {
+            output.extend_from_slice(left_side_bearings);
+        }
+        output[0] = flags;
+        Some(output)
+    }
+
+    /// Rebuild `hmtx` from its transformed form, restoring omitted side bearings from `xMin`.
+    pub(super) fn reconstruct(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let num_glyphs = self.x_mins.len();
+        let num_h_metrics = self
+            .num_h_metrics()
+            .ok_or(Error::InvalidWoff2("invalid hhea.numberOfHMetrics"))?;
+
+        let mut cursor = Cursor::new(data);
+        let truncated = |_| Error::InvalidWoff2("transformed hmtx truncated");
+        let flags = cursor.read_u8().map_err(truncated)?;
+        if flags & !(PROPORTIONAL_LSB_ABSENT | MONOSPACED_LSB_ABSENT) != 0 {
+            return Err(Error::InvalidWoff2("reserved hmtx transform flags set"));
+        }
+        if flags == 0 {
+            return Err(Error::InvalidWoff2("transformed hmtx omits no side bearings"));
+        }
+
+        let advances = (0..num_h_metrics)
+            .map(|_| cursor.read_u16::<BigEndian>().map_err(truncated))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut read_lsbs = |absent: bool, x_mins: &[i16]| {
+            x_mins
+                .iter()
+                .map(|&x_min| {
+                    if absent {
+                        Ok(x_min)
+                    } else {
+                        cursor.read_i16::<BigEndian>().map_err(truncated)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let (proportional, monospaced) = self.x_mins.split_at(num_h_metrics);
+        let lsbs = read_lsbs(flags & PROPORTIONAL_LSB_ABSENT != 0, proportional)?;
+        let left_side_bearings = read_lsbs(flags & MONOSPACED_LSB_ABSENT != 0, monospaced)?;
+        if cursor.position() as usize != data.len() {
+            return Err(Error::InvalidWoff2("transformed hmtx has trailing data"));
+        }
+
+        let mut hmtx = Vec::with_capacity(4 * num_h_metrics + 2 * (num_glyphs - num_h_metrics));
+        for (advance, lsb) in advances.iter().zip(&lsbs) {
+            hmtx.extend_from_slice(&advance.to_be_bytes());
+            hmtx.extend_from_slice(&lsb.to_be_bytes());
+        }
+        for lsb in &left_side_bearings {
+            hmtx.extend_from_slice(&lsb.to_be_bytes());
+        }
+        Ok(hmtx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hhea(num_h_metrics: u16) -> Vec<u8> {
+        let mut hhea = vec![0u8; 36];
+        hhea[34..36].copy_from_slice(&num_h_metrics.to_be_bytes());
+        hhea
+    }
+
+    fn hmtx(metrics: &[(u16, i16)], left_side_bearings: &[i16]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (advance, lsb) in metrics {
+            data.extend_from_slice(&advance.to_be_bytes());
+            data.extend_from_slice(&lsb.to_be_bytes());
+        }
+        for lsb in left_side_bearings {
+            data.extend_from_slice(&lsb.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_transform_roundtrip() {
+        let hhea = hhea(3);
+        let x_mins = [0, 10, -5, 20, 7];
+        let context = HmtxContext { hhea: &hhea, x_mins: &x_mins };
+
+        // Proportional side bearings match xMin, monospaced ones do not
+        let original = hmtx(&[(500, 0), (600, 10), (700, -5)], &[20, 8]);
+        let transformed = context.transform(&original).unwrap();
+        assert_eq!(transformed[0], PROPORTIONAL_LSB_ABSENT);
+        assert_eq!(transformed.len(), 1 + 3 * 2 + 2 * 2);
+        assert_eq!(context.reconstruct(&transformed).unwrap(), original);
+
+        let original = hmtx(&[(500, 0), (600, 11), (700, -5)], &[20, 7]);
+        let transformed = context.transform(&original).unwrap();
+        assert_eq!(transformed[0], MONOSPACED_LSB_ABSENT);
+        assert_eq!(context.reconstruct(&transformed).unwrap(), original);
+    }
+
+    #[test]
+    fn test_transform_skips_tables_without_savings() {
+        let hhea = hhea(2);
+        let x_mins = [0, 10, 20];
+        let context = HmtxContext { hhea: &hhea, x_mins: &x_mins };
+        assert!(context.transform(&hmtx(&[(500, 1), (600, 10)], &[21])).is_none());
+        // Trailing data after the left side bearings would be lost
+        let mut padded = hmtx(&[(500, 0), (600, 10)], &[20]);
+        padded.extend_from_slice(&[0, 0]);
+        assert!(context.transform(&padded).is_none());
+    }
+
+    #[test]
+    fn test_reconstruct_rejects_invalid_data() {
+        let hhea = hhea(1);
+        let x_mins = [0, 10];
+        let context = HmtxContext { hhea: &hhea, x_mins: &x_mins };
+        assert!(context.reconstruct(&[0, 1, 0, 0, 0, 0, 10]).is_err());
+        assert!(context.reconstruct(&[0x04, 1, 0]).is_err());
+        assert!(context.reconstruct(&[0x03, 1]).is_err());
+        assert!(context.reconstruct(&[0x03, 1, 0, 0]).is_err());
+        assert_eq!(context.reconstruct(&[0x03, 1, 0]).unwrap(), [1, 0, 0, 0, 0, 10]);
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::Error;
+
+/// How far an encode has come, passed to a [`ProgressHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// `done` of the `total` glyphs of a `glyf` table have been transformed. Reported every few
+    /// hundred glyphs, and once more when the table is done.
+    Glyphs { done: usize, total: usize },
+    /// `done` of the `total` bytes of font data have been Brotli-compressed. Reported after each
+    /// metablock.
+    Compressed { done: u64, total: u64 },
+}
+
+/// A callback receiving the [`Progress`] of an encode; see [`EncodeOptions::progress`].
+///
+/// The callback runs on the encoding thread, so it should return quickly, e.g. by sending the
+/// progress to a UI thread.
+///
+/// [`EncodeOptions::progress`]: super::encoder::EncodeOptions::progress
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressHook {
+    pub fn new(hook: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// A callback polled during an encode, which aborts it with [`Error::Cancelled`] by returning
+/// `true`; see [`EncodeOptions::cancel`].
+///
+/// [`EncodeOptions::cancel`]: super::encoder::EncodeOptions::cancel
+#[derive(Clone)]
+pub struct CancelHook(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl CancelHook {
+    pub fn new(hook: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for CancelHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CancelHook")
+    }
+}
+
+/// The hooks of an encode, along with the flag set when an async encode is dropped.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Hooks<'h> {
+    pub progress: Option<&'h ProgressHook>,
+    pub cancel: Option<&'h CancelHook>,
+    pub cancelled: Option<&'h AtomicBool>,
+}
+
+impl Hooks<'_> {
+    /// Report progress, then fail with [`Error::Cancelled`] if the encode has been cancelled.
+    pub fn report(&self, progress: Progress) -> Result<(), Error> {
+        if let Some(hook) = self.progress {
+            (hook.0)(progress);
+        }
+        self.check_cancelled()
+    }
+
+    pub fn check_cancelled(&self) -> Result<(), Error> {
+        let cancelled = self
+            .cancelled
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+            || self.cancel.is_some_and(|hook| (hook.0)());
+        if cancelled { Err(Error::Cancelled) } else { Ok(()) }
+    }
+}
@@ -0,0 +1,150 @@
+use super::{
+    decoder::{Decoder, read_metadata, read_private_data},
+    directory::TableDirectoryEntry,
+    tag::Tag,
+};
+use crate::Error;
+
+/// The contents of a WOFF2 file as described by its header and directories, without
+/// decompressing the font data.
+///
+/// Built from the raw file with `Woff2Info::try_from`:
+///
+/// ```no_run
+/// use ttf2woff2::Woff2Info;
+///
+/// let woff2_data = std::fs::read("font.woff2").unwrap();
+/// let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+/// for table in &info.tables {
+///     println!("{} {} -> {}", table.tag, table.orig_length, table.stored_length());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Woff2Info {
+    /// The `flavor` of the encoded font: `0x00010000` for TrueType, `OTTO` for CFF, or `ttcf`
+    /// for a collection.
+    pub flavor: u32,
+    /// Total size of the WOFF2 file.
+    pub length: u32,
+    /// Size of the decoded font, as recorded by the encoder.
+    pub total_sfnt_size: u32,
+    /// Size of the Brotli stream holding all table data.
+    ///
+    /// The tables share a single stream, so there is no per-table compressed size.
+    pub total_compressed_size: u32,
+    /// Font version (`major_version`, `minor_version`), usually copied from `head.fontRevision`.
+    pub version: (u16, u16),
+    /// Offset of the extended metadata block, or 0 when there is none.
+    pub meta_offset: u32,
+    /// Compressed length of the extended metadata block.
+    pub meta_length: u32,
+    /// Uncompressed length of the extended metadata block.
+    pub meta_orig_length: u32,
+    /// Offset of the private data block, or 0 when there is none.
+    pub priv_offset: u32,
+    /// Length of the private data block.
+    pub priv_length: u32,
+    /// The table directory, in the order tables are stored in the font data.
+    pub tables: Vec<TableInfo>,
+    /// The collection directory, for WOFF2 collections.
+    pub collection: Option<CollectionInfo>,
+    /// The decompressed extended metadata XML.
+    pub metadata: Option<String>,
+    /// The contents of the private data block.
+    pub private_data: Option<Vec<u8>>,
+}
+
+/// A WOFF2 table directory entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub tag: Tag,
+    /// Length of the table in the decoded font.
+    pub orig_length: u32,
+    /// Transformation version from the entry's flags byte. Whether a version means "no
+    /// transformation" depends on the table: 3 for `glyf`/`loca`, 0 for everything else.
+    pub transform_version: u8,
+    /// Length of the transformed table, present only when the table is transformed.
+    pub transform_length: Option<u32>,
+}
+
+impl TableInfo {
+    /// Whether the table is stored transformed.
+    pub fn is_transformed(&self) -> bool {
+        self.transform_length.is_some()
+    }
+
+    /// Length of the table data within the decompressed font data stream.
+    pub fn stored_length(&self) -> u32 {
+        self.transform_length.unwrap_or(self.orig_length)
+    }
+}
+
+/// A WOFF2 collection directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionInfo {
+    /// TTC header version: `0x00010000` or `0x00020000`.
+    pub version: u32,
+    pub fonts: Vec<CollectionFontInfo>,
+}
+
+/// A font within a WOFF2 collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionFontInfo {
+    pub flavor: u32,
+    /// Indices of the font's tables in [`Woff2Info::tables`].
+    pub table_indices: Vec<usize>,
+}
+
+impl From<&TableDirectoryEntry> for TableInfo {
+    fn from(entry: &TableDirectoryEntry) -> Self {
+        Self {
+            tag: entry.tag,
+            orig_length: entry.orig_length,
+            transform_version: entry.transform_version,
+            transform_length: entry.transform_length,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Woff2Info {
+    type Error = Error;
+
+    /// Parse the header, table directory and collection directory of a WOFF2 file, along with
+    /// its metadata and private data blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the header or a directory is malformed, or the metadata block
+    /// cannot be decompressed.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let decoder = Decoder::new(data)?;
+        let header = &decoder.header;
+
+        Ok(Self {
+            flavor: header.flavor,
+            length: header.length,
+            total_sfnt_size: header.total_sfnt_size,
+            total_compressed_size: header.total_compressed_size,
+            version: (header.major_version, header.minor_version),
+            meta_offset: header.meta_offset,
+            meta_length: header.meta_length,
+            meta_orig_length: header.meta_orig_length,
+            priv_offset: header.priv_offset,
+            priv_length: header.priv_length,
+            tables: decoder.entries.iter().map(TableInfo::from).collect(),
+            collection: decoder.collection.as_ref().map(|collection| CollectionInfo {
+                version: collection.version,
+                fonts: collection
+                    .fonts
+                    .iter()
+                    .map(|(flavor, indices)| CollectionFontInfo {
+                        flavor: *flavor,
+                        table_indices: indices.clone(),
+                    })
+                    .collect(),
+            }),
+            metadata: read_metadata(data, header)?,
+            private_data: read_private_data(data, header)?.map(<[u8]>::to_vec),
+        })
+    }
+}
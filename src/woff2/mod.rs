@@ -1,13 +1,51 @@
+#[cfg(feature = "async")]
+mod async_encode;
+mod brotli_block_size;
+mod brotli_mode;
 mod brotli_quality;
+mod brotli_window;
+mod cache;
+mod decoder;
 mod directory;
 mod encoder;
+mod glyf;
 mod header;
+mod hinting;
+mod hmtx;
+mod hooks;
+mod info;
 mod inline_bytes;
+mod optimize;
+mod report;
 mod sfnt;
+mod shard;
+mod subset;
+mod table_filter;
 mod tag;
 mod transform;
+// The triplet tests spell out expected flags term by term, as in the spec's triplet table
+#[cfg_attr(test, allow(clippy::identity_op))]
 mod triplet;
 mod varint;
+mod woff1;
 
+#[cfg(feature = "async")]
+pub use async_encode::encode_async;
+pub use brotli_block_size::BrotliBlockSize;
+pub use brotli_mode::BrotliMode;
 pub use brotli_quality::BrotliQuality;
-pub use encoder::{EncodeOptions, encode, encode_no_transform, encode_with_options};
+pub use brotli_window::BrotliWindow;
+pub use cache::EncodeCache;
+pub use decoder::{decode, decode_metadata, decode_private_data};
+pub use encoder::{
+    EncodeOptions, encode, encode_no_transform, encode_to, encode_with_options, encode_with_report,
+};
+pub use hooks::{CancelHook, Progress, ProgressHook};
+pub use info::{CollectionFontInfo, CollectionInfo, TableInfo, Woff2Info};
+pub use optimize::{Candidate, OptimizeOptions, Optimized, encode_optimized};
+pub use report::{EncodeReport, GlyfStreams, TableReport};
+pub use shard::{Shard, ShardPlan, encode_shards, font_face_css};
+pub use subset::SubsetSpec;
+pub use table_filter::TableFilter;
+pub use tag::Tag;
+pub use woff1::encode_woff1;
@@ -0,0 +1,191 @@
+use std::{
+    fmt,
+    num::NonZeroUsize,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use super::{
+    brotli_block_size::BrotliBlockSize,
+    brotli_quality::BrotliQuality,
+    brotli_window::BrotliWindow,
+    encoder::{EncodeOptions, Encoder},
+};
+use crate::Error;
+
+/// The encode settings [`encode_optimized`] chooses from: every combination of the listed values
+/// is tried.
+///
+/// The default tries 8 combinations at quality 11: the `glyf`/`loca` transform on and off, 4 MiB
+/// and 16 MiB windows, and automatic and 16 MiB input blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizeOptions {
+    /// Values of [`EncodeOptions::transform_glyf_loca`] to try.
+    pub transform_glyf_loca: Vec<bool>,
+    /// Brotli qualities to try.
+    pub qualities: Vec<BrotliQuality>,
+    /// Brotli window sizes to try.
+    pub lgwins: Vec<BrotliWindow>,
+    /// Brotli input block sizes to try.
+    pub lgblocks: Vec<BrotliBlockSize>,
+    /// Number of candidates to encode at the same time; `None` (default) uses all cores.
+    ///
+    /// Every candidate is compressed single-threaded, so [`EncodeOptions::threads`] is ignored
+    /// and the result does not depend on this setting.
+    pub parallelism: Option<NonZeroUsize>,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            transform_glyf_loca: vec![true, false],
+            qualities: vec![BrotliQuality::from(11)],
+            lgwins: vec![BrotliWindow::default(), BrotliWindow::try_from(24).unwrap()],
+            lgblocks: vec![BrotliBlockSize::AUTO, BrotliBlockSize::try_from(24).unwrap()],
+            parallelism: None,
+        }
+    }
+}
+
+/// One combination of encode settings tried by [`encode_optimized`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub transform_glyf_loca: bool,
+    pub quality: BrotliQuality,
+    pub lgwin: BrotliWindow,
+    pub lgblock: BrotliBlockSize,
+}
+
+/// Formatted as `transform on, quality 11, lgwin 24, lgblock auto`.
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let transform = if self.transform_glyf_loca { "on" } else { "off" };
+        write!(
+            f,
+            "transform {transform}, quality {}, lgwin {}, lgblock {}",
+            u8::from(self.quality),
+            self.lgwin,
+            self.lgblock
+        )
+    }
+}
+
+/// The smallest WOFF2 file found by [`encode_optimized`].
+#[derive(Debug, Clone)]
+pub struct Optimized {
+    /// The WOFF2 file.
+    pub data: Vec<u8>,
+    /// The settings that produced it.
+    pub winner: Candidate,
+    /// The output size of every candidate that could be encoded, in the order they were listed.
+    pub sizes: Vec<(Candidate, usize)>,
+}
+
+/// Encode a font with every combination of settings in `optimize` and keep the smallest output.
+///
+/// `options` provides everything else, such as subsetting or metadata; its quality, window,
+/// block size and transform settings are replaced by those of each candidate. The font is
+/// parsed, subset and filtered once and shared by all candidates. On a tie, the candidate listed
+/// first wins, so the result is deterministic.
+///
+/// The [`EncodeOptions::progress`] hook receives the progress of every candidate, from several
+/// threads at once when candidates are encoded in parallel.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the font cannot be parsed or prepared, or the error of the first
+/// candidate if no candidate could be encoded. An empty search space returns
+/// [`Error::InvalidOptions`].
+pub fn encode_optimized(
+    ttf_data: &[u8],
+    options: EncodeOptions,
+    optimize: &OptimizeOptions,
+) -> Result<Optimized, Error> {
+    let mut candidates = Vec::new();
+    for &transform_glyf_loca in &optimize.transform_glyf_loca {
+        for &quality in &optimize.qualities {
+            for &lgwin in &optimize.lgwins {
+                for &lgblock in &optimize.lgblocks {
+                    candidates.push(Candidate { transform_glyf_loca, quality, lgwin, lgblock });
+                }
+            }
+        }
+    }
+    if candidates.is_empty() {
+        return Err(Error::InvalidOptions("no candidate settings to try"));
+    }
+
+    let encoder = Encoder::new(ttf_data, options.clone())?;
+    let encode = |candidate: &Candidate| -> Result<Vec<u8>, Error> {
+        let options = EncodeOptions {
+            quality: candidate.quality,
+            lgwin: candidate.lgwin,
+            lgblock: candidate.lgblock,
+            transform_glyf_loca: candidate.transform_glyf_loca,
+            threads: None,
+            ..options.clone()
+        };
+        encoder.with_options(options).try_into()
+    };
+
+    // WASM cannot spawn OS threads, so candidates are encoded one after another there
+    let workers = if cfg!(target_family = "wasm") {
+        1
+    } else {
+        optimize
+            .parallelism
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(candidates.len())
+    };
+    let results: Vec<_> = if workers <= 1 {
+        candidates.iter().map(encode).collect()
+    } else {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..candidates.len()).map(|_| None).collect::<Vec<_>>());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(candidate) = candidates.get(index) else {
+                            break;
+                        };
+                        let result = encode(candidate);
+                        results.lock().unwrap()[index] = Some(result);
+                    }
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
+    };
+
+    let mut best: Option<(Candidate, Vec<u8>)> = None;
+    let mut sizes = Vec::with_capacity(candidates.len());
+    let mut first_error = None;
+    for (candidate, result) in candidates.into_iter().zip(results) {
+        match result {
+            Ok(data) => {
+                sizes.push((candidate, data.len()));
+                if best.as_ref().is_none_or(|(_, best)| data.len() < best.len()) {
+                    best = Some((candidate, data));
+                }
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match best {
+        Some((winner, data)) => Ok(Optimized { data, winner, sizes }),
+        None => Err(first_error.expect("every candidate failed")),
+    }
+}
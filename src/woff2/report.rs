@@ -0,0 +1,86 @@
+use super::tag::Tag;
+
+/// A breakdown of where the bytes of an encoded WOFF2 file go, returned by
+/// [`encode_with_report`](crate::encode_with_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeReport {
+    /// Size of the WOFF2 file.
+    pub length: u32,
+    /// Size of the font the WOFF2 file decodes to, as recorded in the `totalSfntSize` header
+    /// field.
+    pub total_sfnt_size: u32,
+    /// Size of the WOFF2 header and table directory, including the collection directory for
+    /// collections.
+    pub directory_length: u32,
+    /// Size of the Brotli stream holding the data of every table.
+    pub total_compressed_size: u32,
+    /// Size of the compressed extended metadata block, or 0 if there is none.
+    pub metadata_length: u32,
+    /// Size of the private data block, or 0 if there is none.
+    pub private_data_length: u32,
+    /// Every table, in table directory order.
+    pub tables: Vec<TableReport>,
+}
+
+/// The sizes of one table in an [`EncodeReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableReport {
+    pub tag: Tag,
+    /// Length of the table in the input font.
+    pub orig_length: u32,
+    /// Length of the transformed table, or `None` if the table is stored as-is.
+    pub transform_length: Option<u32>,
+    /// Size of the table's stored data when Brotli-compressed on its own, with the same quality.
+    pub brotli_length: u32,
+    /// Estimated share of [`EncodeReport::total_compressed_size`].
+    ///
+    /// All tables share one Brotli stream, which cannot be split by table, so this is
+    /// `brotli_length` scaled for the shares to add up to the stream size. The scaling spreads
+    /// the savings from redundancy across tables evenly, whichever tables they come from.
+    pub compressed_share: u32,
+    /// Sizes of the streams of a transformed `glyf` table.
+    pub glyf_streams: Option<GlyfStreams>,
+}
+
+impl TableReport {
+    /// Length of the data stored for the table: the transformed length if the table is
+    /// transformed, otherwise the original length.
+    pub fn stored_length(&self) -> u32 {
+        self.transform_length.unwrap_or(self.orig_length)
+    }
+}
+
+/// Sizes of the streams of a [transformed `glyf` table](https://www.w3.org/TR/WOFF2/#glyf_table_format),
+/// as recorded in its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyfStreams {
+    /// Number of contours of every glyph.
+    pub n_contour: u32,
+    /// Number of points of every contour.
+    pub n_points: u32,
+    /// Point flags.
+    pub flag: u32,
+    /// Point coordinates and instruction lengths.
+    pub glyph: u32,
+    /// Composite glyph component records.
+    pub composite: u32,
+    /// Explicit bounding boxes, including the bitmap of glyphs that have one.
+    pub bbox: u32,
+    /// TrueType instructions.
+    pub instruction: u32,
+}
+
+impl GlyfStreams {
+    /// The stream sizes with their names from the WOFF2 specification.
+    pub fn named(&self) -> [(&'static str, u32); 7] {
+        [
+            ("nContour", self.n_contour),
+            ("nPoints", self.n_points),
+            ("flag", self.flag),
+            ("glyph", self.glyph),
+            ("composite", self.composite),
+            ("bbox", self.bbox),
+            ("instruction", self.instruction),
+        ]
+    }
+}
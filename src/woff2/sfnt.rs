@@ -6,23 +6,115 @@ use super::tag::Tag;
 use crate::{Error, Error::DataTooShort};
 
 const TTF_FLAVOR: u32 = 0x00010000;
+const CFF_FLAVOR: u32 = 0x4F54544F; // 'OTTO'
+pub(crate) const COLLECTION_FLAVOR: u32 = 0x74746366; // 'ttcf'
+const CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
 
+#[derive(Clone)]
 pub(crate) struct SfntTable {
     pub tag: Tag,
+    pub checksum: u32,
     pub offset: u32,
     pub length: u32,
 }
 
+#[derive(Clone)]
 pub(crate) struct Sfnt {
     pub flavor: u32,
     pub tables: Vec<SfntTable>,
 }
 
-impl TryFrom<&[u8]> for Sfnt {
-    type Error = Error;
+/// A parsed font file: either a single SFNT or a TrueType collection of several.
+#[derive(Clone)]
+pub(crate) enum FontFile {
+    Single(Sfnt),
+    Collection { version: u32, fonts: Vec<Sfnt> },
+}
 
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+impl FontFile {
+    pub fn fonts(&self) -> &[Sfnt] {
+        match self {
+            Self::Single(sfnt) => std::slice::from_ref(sfnt),
+            Self::Collection { fonts, .. } => fonts,
+        }
+    }
+
+    /// Remove the tables for which `keep` returns `false` from every font.
+    pub fn retain_tables(&mut self, mut keep: impl FnMut(&SfntTable) -> bool) {
+        let fonts = match self {
+            Self::Single(sfnt) => std::slice::from_mut(sfnt),
+            Self::Collection { fonts, .. } => fonts,
+        };
+        for sfnt in fonts {
+            sfnt.tables.retain(&mut keep);
+        }
+    }
+
+    /// Verify every table checksum and, for a single font, `head.checkSumAdjustment`.
+    ///
+    /// The adjustment is not checked in collections, where a `head` table shared by several
+    /// fonts can only hold the adjustment of one of them.
+    pub fn verify_checksums(&self, data: &[u8]) -> Result<(), Error> {
+        for sfnt in self.fonts() {
+            for table in &sfnt.tables {
+                let actual = table_checksum(table.tag, table_data(data, table));
+                if actual != table.checksum {
+                    return Err(Error::ChecksumMismatch {
+                        tag: table.tag,
+                        expected: table.checksum,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        let Self::Single(sfnt) = self else {
+            return Ok(());
+        };
+        let Some(head) = sfnt.tables.iter().find(|t| t.tag.is_head() && t.length >= 12) else {
+            return Ok(());
+        };
+        let head_data = table_data(data, head);
+        let expected =
+            u32::from_be_bytes([head_data[8], head_data[9], head_data[10], head_data[11]]);
+        let directory_end = 12 + 16 * sfnt.tables.len();
+        let font_checksum = sfnt
+            .tables
+            .iter()
+            .fold(checksum(&data[..directory_end]), |acc, t| acc.wrapping_add(t.checksum));
+        let actual = CHECKSUM_MAGIC.wrapping_sub(font_checksum);
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { tag: head.tag, expected, actual });
+        }
+        Ok(())
+    }
+}
+
+fn table_data<'a>(data: &'a [u8], table: &SfntTable) -> &'a [u8] {
+    let start = table.offset as usize;
+    &data[start..start + table.length as usize]
+}
+
+/// The checksum of a table as recorded in the table directory, which for `head` is computed with
+/// `checkSumAdjustment` treated as zero.
+pub(crate) fn table_checksum(tag: Tag, data: &[u8]) -> u32 {
+    let sum = checksum(data);
+    match data.get(8..12) {
+        Some(adjustment) if tag.is_head() => sum.wrapping_sub(u32::from_be_bytes([
+            adjustment[0],
+            adjustment[1],
+            adjustment[2],
+            adjustment[3],
+        ])),
+        _ => sum,
+    }
+}
+
+impl Sfnt {
+    /// Parse the offset table and table records starting at `start`.
+    fn parse(data: &[u8], start: usize) -> Result<Self, Error> {
         let mut cursor = Cursor::new(data);
+        cursor.set_position(start as u64);
 
         let flavor = cursor
             .read_u32::<BigEndian>()
@@ -32,9 +124,9 @@ impl TryFrom<&[u8]> for Sfnt {
             .map_err(|_| DataTooShort { context: "SFNT header" })?
             as usize;
 
-        cursor.set_position(12);
+        cursor.set_position(start as u64 + 12);
 
-        if flavor != TTF_FLAVOR {
+        if flavor != TTF_FLAVOR && flavor != CFF_FLAVOR {
             return Err(Error::UnsupportedFormat);
         }
 
@@ -44,7 +136,7 @@ impl TryFrom<&[u8]> for Sfnt {
             cursor
                 .read_exact(&mut tag_bytes)
                 .map_err(|_| DataTooShort { context: "table directory" })?;
-            let _checksum = cursor
+            let checksum = cursor
                 .read_u32::<BigEndian>()
                 .map_err(|_| DataTooShort { context: "table directory" })?;
             let offset = cursor
@@ -59,9 +151,252 @@ impl TryFrom<&[u8]> for Sfnt {
                 return Err(Error::TableOutOfBounds);
             }
 
-            tables.push(SfntTable { tag: Tag(tag_bytes), offset, length });
+            tables.push(SfntTable { tag: Tag(tag_bytes), checksum, offset, length });
         }
 
         Ok(Self { flavor, tables })
     }
 }
+
+impl TryFrom<&[u8]> for Sfnt {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, 0)
+    }
+}
+
+impl TryFrom<&[u8]> for FontFile {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if !data.starts_with(&COLLECTION_FLAVOR.to_be_bytes()) {
+            return Sfnt::try_from(data).map(Self::Single);
+        }
+
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(4);
+        let version = cursor
+            .read_u32::<BigEndian>()
+            .map_err(|_| DataTooShort { context: "TTC header" })?;
+        let num_fonts = cursor
+            .read_u32::<BigEndian>()
+            .map_err(|_| DataTooShort { context: "TTC header" })?;
+        if num_fonts == 0 || num_fonts > u32::from(u16::MAX) {
+            return Err(Error::UnsupportedFormat);
+        }
+
+        let mut fonts = Vec::with_capacity(num_fonts as usize);
+        for _ in 0..num_fonts {
+            let offset = cursor
+                .read_u32::<BigEndian>()
+                .map_err(|_| DataTooShort { context: "TTC header" })?;
+            fonts.push(Sfnt::parse(data, offset as usize)?);
+        }
+
+        Ok(Self::Collection { version, fonts })
+    }
+}
+
+/// OpenType table checksum: the wrapping sum of big-endian `u32` words, zero-padded at the end.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(4);
+    let mut sum = chunks
+        .by_ref()
+        .fold(0u32, |acc, c| acc.wrapping_add(u32::from_be_bytes([c[0], c[1], c[2], c[3]])));
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        let mut last = [0u8; 4];
+        last[..rest.len()].copy_from_slice(rest);
+        sum = sum.wrapping_add(u32::from_be_bytes(last));
+    }
+    sum
+}
+
+/// Serialize tables into a single-font SFNT file.
+///
+/// Table data is written in the given order, each padded to a 4-byte boundary, while the table
+/// records are sorted by tag as the OpenType spec requires. Table checksums are computed from the
+/// written data and `head.checkSumAdjustment` is recomputed for the whole font.
+pub(crate) fn write_sfnt(flavor: u32, tables: &[(Tag, &[u8])]) -> Vec<u8> {
+    let indices = (0..tables.len()).collect();
+    write_fonts(None, &[(flavor, indices)], tables)
+}
+
+/// Serialize a TrueType collection.
+///
+/// `fonts` lists each font's flavor and the indices of its tables in `tables`; a table referenced
+/// by several fonts is written once and shared. Checksums are computed as in [`write_sfnt`], with
+/// `head.checkSumAdjustment` computed per font.
+pub(crate) fn write_collection(
+    version: u32,
+    fonts: &[(u32, Vec<usize>)],
+    tables: &[(Tag, &[u8])],
+) -> Vec<u8> {
+    write_fonts(Some(version), fonts, tables)
+}
+
+fn write_fonts(
+    collection_version: Option<u32>,
+    fonts: &[(u32, Vec<usize>)],
+    tables: &[(Tag, &[u8])],
+) -> Vec<u8> {
+    let header_size = match collection_version {
+        Some(version) => 12 + 4 * fonts.len() + if version >= 0x00020000 { 12 } else { 0 },
+        None => 0,
+    };
+    let mut directory_offsets = Vec::with_capacity(fonts.len());
+    let mut directories_end = header_size;
+    for (_, indices) in fonts {
+        directory_offsets.push(directories_end);
+        directories_end += 12 + 16 * indices.len();
+    }
+
+    let data_size: usize = tables.iter().map(|(_, data)| (data.len() + 3) & !3).sum();
+    let mut out = Vec::with_capacity(directories_end + data_size);
+    if let Some(version) = collection_version {
+        out.extend_from_slice(&COLLECTION_FLAVOR.to_be_bytes());
+        out.extend_from_slice(&version.to_be_bytes());
+        out.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+        for &offset in &directory_offsets {
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+    out.resize(directories_end, 0);
+
+    // (checksum, offset) of each table
+    let mut records = Vec::with_capacity(tables.len());
+    for &(tag, data) in tables {
+        let offset = out.len();
+        out.extend_from_slice(data);
+        if tag.is_head() && data.len() >= 12 {
+            out[offset + 8..offset + 12].fill(0);
+        }
+        records.push((checksum(&out[offset..]), offset));
+        out.resize((out.len() + 3) & !3, 0);
+    }
+
+    for ((flavor, indices), &start) in fonts.iter().zip(&directory_offsets) {
+        let mut sorted = indices.clone();
+        sorted.sort_by_key(|&i| tables[i].0);
+
+        let num_tables = sorted.len() as u16;
+        let entry_selector = num_tables.checked_ilog2().unwrap_or(0) as u16;
+        let search_range = (1u16 << entry_selector) * 16;
+        let range_shift = (num_tables * 16).saturating_sub(search_range);
+        out[start..start + 4].copy_from_slice(&flavor.to_be_bytes());
+        out[start + 4..start + 6].copy_from_slice(&num_tables.to_be_bytes());
+        out[start + 6..start + 8].copy_from_slice(&search_range.to_be_bytes());
+        out[start + 8..start + 10].copy_from_slice(&entry_selector.to_be_bytes());
+        out[start + 10..start + 12].copy_from_slice(&range_shift.to_be_bytes());
+
+        for (j, &i) in sorted.iter().enumerate() {
+            let (tag, data) = tables[i];
+            let (table_checksum, offset) = records[i];
+            let pos = start + 12 + 16 * j;
+            out[pos..pos + 4].copy_from_slice(&tag.0);
+            out[pos + 4..pos + 8].copy_from_slice(&table_checksum.to_be_bytes());
+            out[pos + 8..pos + 12].copy_from_slice(&(offset as u32).to_be_bytes());
+            out[pos + 12..pos + 16].copy_from_slice(&(data.len() as u32).to_be_bytes());
+        }
+
+        // The font checksum is the sum of its offset table, its table records and every table it
+        // references (head with checkSumAdjustment zeroed), since all tables are 4-byte aligned.
+        if let Some(&head) = sorted
+            .iter()
+            .find(|&&i| tables[i].0.is_head() && tables[i].1.len() >= 12)
+        {
+            let end = start + 12 + 16 * sorted.len();
+            let font_checksum = sorted
+                .iter()
+                .fold(checksum(&out[start..end]), |acc, &i| acc.wrapping_add(records[i].0));
+            let adjustment = CHECKSUM_MAGIC.wrapping_sub(font_checksum);
+            let offset = records[head].1;
+            out[offset + 8..offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_pads_trailing_bytes() {
+        assert_eq!(checksum(&[]), 0);
+        assert_eq!(checksum(&[0, 0, 0, 1, 0, 0, 0, 2]), 3);
+        assert_eq!(checksum(&[1]), 0x0100_0000);
+        assert_eq!(checksum(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 2]), 1);
+    }
+
+    #[test]
+    fn test_write_sfnt_roundtrip() {
+        let mut head = vec![0u8; 54];
+        head[8..12].copy_from_slice(&0xDEADBEEFu32.to_be_bytes());
+        let name = b"abcde".to_vec();
+        let font = write_sfnt(TTF_FLAVOR, &[(Tag(*b"name"), &name), (Tag::HEAD, &head)]);
+
+        assert_eq!(font.len() % 4, 0);
+        assert_eq!(checksum(&font), CHECKSUM_MAGIC);
+
+        let sfnt = Sfnt::try_from(&font[..]).unwrap();
+        let tags: Vec<_> = sfnt.tables.iter().map(|t| t.tag).collect();
+        assert_eq!(tags, [Tag::HEAD, Tag(*b"name")]);
+        let name_table = &sfnt.tables[1];
+        let start = name_table.offset as usize;
+        assert_eq!(&font[start..start + name_table.length as usize], b"abcde");
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        let mut head = vec![0u8; 54];
+        head[12..16].copy_from_slice(&0x5F0F3CF5u32.to_be_bytes());
+        let name = b"abcde".to_vec();
+        let font = write_sfnt(TTF_FLAVOR, &[(Tag(*b"name"), &name), (Tag::HEAD, &head)]);
+        let sfnt = FontFile::try_from(&font[..]).unwrap();
+        sfnt.verify_checksums(&font).unwrap();
+
+        let FontFile::Single(parsed) = &sfnt else { unreachable!() };
+        let (head, name) = (&parsed.tables[0], &parsed.tables[1]);
+
+        let mut corrupted = font.clone();
+        corrupted[name.offset as usize] ^= 1;
+        assert!(matches!(
+            sfnt.verify_checksums(&corrupted),
+            Err(Error::ChecksumMismatch { tag, .. }) if tag == Tag(*b"name")
+        ));
+
+        // The adjustment is excluded from the head checksum but not from the whole-font one
+        let mut corrupted = font.clone();
+        corrupted[head.offset as usize + 11] ^= 1;
+        assert!(matches!(
+            sfnt.verify_checksums(&corrupted),
+            Err(Error::ChecksumMismatch { tag: Tag::HEAD, .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_collection_shares_tables() {
+        let head = [0u8; 54];
+        let (name_a, name_b) = (b"regular".to_vec(), b"bold".to_vec());
+        let tables = [(Tag::HEAD, &head[..]), (Tag(*b"name"), &name_a), (Tag(*b"name"), &name_b)];
+        let fonts = [(TTF_FLAVOR, vec![0, 1]), (TTF_FLAVOR, vec![0, 2])];
+        let ttc = write_collection(0x00010000, &fonts, &tables);
+
+        let FontFile::Collection { version, fonts } = FontFile::try_from(&ttc[..]).unwrap() else {
+            panic!("expected a collection");
+        };
+        assert_eq!(version, 0x00010000);
+        assert_eq!(fonts.len(), 2);
+        assert_eq!(fonts[0].tables[0].offset, fonts[1].tables[0].offset);
+        assert_ne!(fonts[0].tables[1].offset, fonts[1].tables[1].offset);
+        let name = &fonts[1].tables[1];
+        let start = name.offset as usize;
+        assert_eq!(&ttc[start..start + name.length as usize], b"bold");
+
+        FontFile::Collection { version, fonts }
+            .verify_checksums(&ttc)
+            .unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+use std::{collections::HashSet, fmt::Write, num::NonZeroUsize, ops::RangeInclusive};
+
+use super::{
+    encoder::{EncodeOptions, encode_with_options},
+    sfnt::FontFile,
+    subset::{SubsetSpec, mapped_codepoints},
+    woff1::{is_woff1, read_woff1},
+};
+use crate::Error;
+
+/// Characters that most pages use, in the order [`ShardPlan::Frequency`] ranks them: ASCII,
+/// Latin-1, common punctuation and symbols, CJK punctuation, kana and fullwidth forms.
+const FREQUENT_RANGES: [RangeInclusive<u32>; 9] = [
+    0x20..=0x7E,
+    0xA0..=0xFF,
+    0x2000..=0x206F,
+    0x20AC..=0x20AC,
+    0x2122..=0x2122,
+    0x3000..=0x303F,
+    0x3041..=0x309F,
+    0x30A0..=0x30FF,
+    0xFF01..=0xFF5E,
+];
+
+/// CJK ideographs in descending order of frequency, interleaving Japanese and Chinese text
+/// frequency rankings. Whitespace is ignored.
+const RANKED_IDEOGRAPHS: &str = include_str!("frequency.txt");
+
+/// How to split a font into `unicode-range` shards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShardPlan {
+    /// One shard per entry, each covering the given code point ranges.
+    Explicit(Vec<Vec<RangeInclusive<u32>>>),
+    /// Split the characters of the font, in code point order, into shards of at most
+    /// `codepoints_per_shard` characters each.
+    ///
+    /// Code point order keeps scripts and Unicode blocks together (Latin before kana before CJK
+    /// ideographs), but CJK ideographs are ordered by radical rather than by how often they are
+    /// used, so a typical page touches many shards. See [`ShardPlan::Frequency`].
+    Sequential { codepoints_per_shard: NonZeroUsize },
+    /// Split the characters of the font, most frequently used first, into shards of at most
+    /// `codepoints_per_shard` characters each.
+    ///
+    /// Characters are ranked by a built-in list: Latin, punctuation and kana first, then about
+    /// 3,000 CJK ideographs in approximate order of frequency in Japanese and Chinese text. The
+    /// font's other characters follow in code point order. The first shards thus hold what most
+    /// pages need, and rare ideographs end up in shards that few pages download, as with the
+    /// slices Google Fonts serves.
+    Frequency { codepoints_per_shard: NonZeroUsize },
+}
+
+impl ShardPlan {
+    /// Resolve the plan against the characters of a font, returning the code point ranges of each
+    /// shard.
+    ///
+    /// Ranges are narrowed to the characters the font maps, and shards left empty are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the font cannot be parsed, is a collection, or has no Unicode
+    /// `cmap` subtable in format 4 or 12.
+    pub fn slices(&self, ttf_data: &[u8]) -> Result<Vec<Vec<RangeInclusive<u32>>>, Error> {
+        let woff;
+        let ttf_data = if is_woff1(ttf_data) {
+            woff = read_woff1(ttf_data, false)?;
+            &woff.sfnt[..]
+        } else {
+            ttf_data
+        };
+        let font = FontFile::try_from(ttf_data)?;
+        let codepoints = mapped_codepoints(ttf_data, &font)?;
+
+        let slices: Vec<Vec<_>> = match self {
+            Self::Explicit(slices) => slices
+                .iter()
+                .map(|ranges| {
+                    let covered = codepoints
+                        .iter()
+                        .copied()
+                        .filter(|codepoint| ranges.iter().any(|range| range.contains(codepoint)));
+                    collapse_ranges(covered)
+                })
+                .collect(),
+            Self::Sequential { codepoints_per_shard } => codepoints
+                .chunks(codepoints_per_shard.get())
+                .map(|chunk| collapse_ranges(chunk.iter().copied()))
+                .collect(),
+            Self::Frequency { codepoints_per_shard } => {
+                let ranked = FREQUENT_RANGES.into_iter().flatten().chain(
+                    RANKED_IDEOGRAPHS
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .map(u32::from),
+                );
+                let mut seen = HashSet::new();
+                let mut ordered: Vec<u32> = ranked
+                    .filter(|codepoint| {
+                        codepoints.binary_search(codepoint).is_ok() && seen.insert(*codepoint)
+                    })
+                    .collect();
+                ordered.extend(codepoints.iter().filter(|codepoint| !seen.contains(codepoint)));
+                ordered
+                    .chunks(codepoints_per_shard.get())
+                    .map(|chunk| {
+                        let mut chunk = chunk.to_vec();
+                        chunk.sort_unstable();
+                        collapse_ranges(chunk)
+                    })
+                    .collect()
+            }
+        };
+        Ok(slices.into_iter().filter(|ranges| !ranges.is_empty()).collect())
+    }
+}
+
+/// A `unicode-range` shard of a font, encoded as WOFF2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shard {
+    /// Code points covered by the shard, sorted and non-overlapping.
+    pub unicodes: Vec<RangeInclusive<u32>>,
+    /// The encoded WOFF2 file.
+    pub data: Vec<u8>,
+}
+
+/// Split a font into WOFF2 shards, one subset per slice of the plan.
+///
+/// Each shard is encoded with `options`, its `subset` replaced by the shard's code points. Serve
+/// the shards together with [`font_face_css`] rules so browsers download only the shards a page
+/// uses.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the plan cannot be resolved (see [`ShardPlan::slices`]) or a shard
+/// fails to encode.
+pub fn encode_shards(
+    ttf_data: &[u8],
+    plan: &ShardPlan,
+    options: EncodeOptions,
+) -> Result<Vec<Shard>, Error> {
+    plan.slices(ttf_data)?
+        .into_iter()
+        .map(|unicodes| {
+            let subset = SubsetSpec {
+                unicodes: unicodes.clone(),
+                ..SubsetSpec::default()
+            };
+            let options = EncodeOptions { subset: Some(subset), ..options.clone() };
+            let data = encode_with_options(ttf_data, options)?;
+            Ok(Shard { unicodes, data })
+        })
+        .collect()
+}
+
+/// Write an `@font-face` rule serving the shard at `url` for the given code points.
+///
+/// ```
+/// let css = ttf2woff2::font_face_css("Noto Sans JP", "noto.0.woff2", &[0x20..=0x7E, 0x3042..=0x3042]);
+/// assert!(css.contains("unicode-range: U+20-7E, U+3042;"));
+/// ```
+pub fn font_face_css(family: &str, url: &str, unicodes: &[RangeInclusive<u32>]) -> String {
+    let quote = |value: &str| value.replace('\\', "\\\\").replace('\'', "\\'");
+    let mut css = String::new();
+    css.push_str("@font-face {\n");
+    let _ = writeln!(css, "  font-family: '{}';", quote(family));
+    let _ = writeln!(css, "  src: url('{}') format('woff2');", quote(url));
+    css.push_str("  font-display: swap;\n");
+    let ranges: Vec<_> = unicodes
+        .iter()
+        .map(|range| match (range.start(), range.end()) {
+            (start, end) if start == end => format!("U+{start:X}"),
+            (start, end) => format!("U+{start:X}-{end:X}"),
+        })
+        .collect();
+    let _ = writeln!(css, "  unicode-range: {};", ranges.join(", "));
+    css.push_str("}\n");
+    css
+}
+
+/// Merge sorted code points into ranges of consecutive code points.
+fn collapse_ranges(codepoints: impl IntoIterator<Item = u32>) -> Vec<RangeInclusive<u32>> {
+    let mut ranges: Vec<RangeInclusive<u32>> = Vec::new();
+    for codepoint in codepoints {
+        match ranges.last_mut() {
+            Some(range) if *range.end() + 1 == codepoint => *range = *range.start()..=codepoint,
+            _ => ranges.push(codepoint..=codepoint),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse_ranges() {
+        assert_eq!(
+            collapse_ranges([0x41, 0x42, 0x43, 0x45, 0x3042]),
+            [0x41..=0x43, 0x45..=0x45, 0x3042..=0x3042]
+        );
+        assert!(collapse_ranges([]).is_empty());
+    }
+
+    #[test]
+    fn test_font_face_css() {
+        let css = font_face_css("It's", "fonts/a.woff2", &[0x41..=0x5A, 0x1F600..=0x1F600]);
+        assert_eq!(
+            css,
+            "@font-face {\n  font-family: 'It\\'s';\n  src: url('fonts/a.woff2') format('woff2');\n  \
+             font-display: swap;\n  unicode-range: U+41-5A, U+1F600;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_ranked_ideographs_are_unique_cjk() {
+        let mut seen = HashSet::new();
+        for c in RANKED_IDEOGRAPHS.chars().filter(|c| !c.is_whitespace()) {
+            assert!(('\u{4E00}'..='\u{9FFF}').contains(&c), "{c} is not a CJK ideograph");
+            assert!(seen.insert(c), "{c} is ranked twice");
+        }
+        assert!(seen.len() > 2500);
+        // The most common ideographs of both languages rank first
+        assert!(RANKED_IDEOGRAPHS.starts_with("日的一国"));
+    }
+}
@@ -0,0 +1,597 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use super::{
+    glyf::{build_glyf_loca, glyph_slices, is_long_loca, read_u16, read_u32},
+    sfnt::{FontFile, SfntTable, write_sfnt},
+    tag::Tag,
+    transform::walk_components,
+};
+use crate::Error;
+
+/// Tables copied into the subset font unchanged, since they do not refer to glyph IDs.
+const COPIED_TABLES: [&[u8; 4]; 6] = [b"cvt ", b"fpgm", b"gasp", b"meta", b"name", b"prep"];
+
+/// The last Unicode code point.
+const MAX_CODEPOINT: u32 = 0x10FFFF;
+
+/// The characters and glyphs to keep when subsetting a font before encoding.
+///
+/// Glyph 0 (`.notdef`) is always kept, along with every glyph referenced by a kept composite
+/// glyph. Kept glyphs are renumbered in their original order.
+///
+/// ```no_run
+/// use ttf2woff2::{EncodeOptions, SubsetSpec, encode_with_options};
+///
+/// let ttf_data = std::fs::read("font.ttf").unwrap();
+/// let subset = SubsetSpec { unicodes: vec![0x20..=0x7E], ..SubsetSpec::default() };
+/// let options = EncodeOptions { subset: Some(subset), ..EncodeOptions::default() };
+/// let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubsetSpec {
+    /// Unicode code points to keep, as inclusive ranges; a single code point `c` is `c..=c`.
+    pub unicodes: Vec<RangeInclusive<u32>>,
+    /// Glyph IDs to keep in addition to those the kept code points map to.
+    pub glyph_ids: Vec<RangeInclusive<u16>>,
+}
+
+/// Subset a single TrueType font, returning the rebuilt SFNT.
+///
+/// `glyf`, `loca`, `hmtx`, `vmtx`, `maxp`, `cmap` and the headers that describe them are rewritten
+/// for the kept glyphs, and `post` is reduced to version 3 (no glyph names). Tables that may refer
+/// to glyph IDs and are not rewritten, such as `GSUB`, `GPOS` or `kern`, are dropped.
+pub(super) fn subset(data: &[u8], font: &FontFile, spec: &SubsetSpec) -> Result<Vec<u8>, Error> {
+    let FontFile::Single(sfnt) = font else {
+        return Err(Error::Subset("font collections are not supported"));
+    };
+    let table = |tag: &[u8; 4]| {
+        sfnt.tables
+            .iter()
+            .find(|t| t.tag.0 == *tag)
+            .map(|t: &SfntTable| &data[t.offset as usize..][..t.length as usize])
+    };
+    if table(b"gvar").is_some() {
+        return Err(Error::Subset("variable fonts are not supported"));
+    }
+    let (Some(glyf), Some(loca), Some(head), Some(maxp), Some(cmap)) =
+        (table(b"glyf"), table(b"loca"), table(b"head"), table(b"maxp"), table(b"cmap"))
+    else {
+        return Err(Error::Subset("glyf, loca, head, maxp and cmap tables are required"));
+    };
+
+    let num_glyphs = read_u16(maxp, 4, "maxp table")? as usize;
+    let glyphs = glyph_slices(glyf, loca, is_long_loca(head)?, num_glyphs)?;
+
+    let mut mapping = parse_cmap(cmap, &spec.unicodes)?;
+    mapping.retain(|_, &mut glyph_id| (glyph_id as usize) < num_glyphs);
+
+    let mut keep = vec![false; num_glyphs];
+    let mut pending = vec![0];
+    pending.extend(mapping.values().copied());
+    pending.extend(spec.glyph_ids.iter().flat_map(Clone::clone));
+    while let Some(glyph_id) = pending.pop() {
+        let Some(kept @ false) = keep.get_mut(glyph_id as usize) else {
+            continue;
+        };
+        *kept = true;
+        let glyph = glyphs[glyph_id as usize];
+        if glyph.len() >= 10 && i16::from_be_bytes([glyph[0], glyph[1]]) < 0 {
+            walk_components(glyph, 10, |pos| {
+                pending.push(u16::from_be_bytes([glyph[pos], glyph[pos + 1]]));
+            })?;
+        }
+    }
+
+    let old_ids: Vec<u16> = (0..num_glyphs as u16).filter(|&id| keep[id as usize]).collect();
+    let mut new_ids = vec![0u16; num_glyphs];
+    for (new_id, &old_id) in old_ids.iter().enumerate() {
+        new_ids[old_id as usize] = new_id as u16;
+    }
+    for glyph_id in mapping.values_mut() {
+        *glyph_id = new_ids[*glyph_id as usize];
+    }
+
+    // glyf and loca, with composite component references renumbered
+    let mut new_glyphs = Vec::with_capacity(old_ids.len());
+    for &old_id in &old_ids {
+        let mut glyph = glyphs[old_id as usize].to_vec();
+        if glyph.len() >= 10 && i16::from_be_bytes([glyph[0], glyph[1]]) < 0 {
+            let mut out_of_range = false;
+            walk_components(glyphs[old_id as usize], 10, |pos| {
+                let old_component = u16::from_be_bytes([glyph[pos], glyph[pos + 1]]);
+                match new_ids.get(old_component as usize) {
+                    Some(new_component) => {
+                        glyph[pos..pos + 2].copy_from_slice(&new_component.to_be_bytes())
+                    }
+                    None => out_of_range = true,
+                }
+            })?;
+            if out_of_range {
+                return Err(Error::InvalidGlyph("component glyph index out of range"));
+            }
+        }
+        new_glyphs.push(glyph);
+    }
+    let (new_glyf, new_loca, long_loca) = build_glyf_loca(&new_glyphs);
+
+    let mut new_head = head.to_vec();
+    new_head[50..52].copy_from_slice(&u16::from(long_loca).to_be_bytes());
+    let mut new_maxp = maxp.to_vec();
+    new_maxp[4..6].copy_from_slice(&(old_ids.len() as u16).to_be_bytes());
+
+    let mut tables: Vec<(Tag, Vec<u8>)> = vec![
+        (Tag(*b"cmap"), build_cmap(&mapping)),
+        (Tag::GLYF, new_glyf),
+        (Tag::LOCA, new_loca),
+        (Tag::HEAD, new_head),
+        (Tag::MAXP, new_maxp),
+    ];
+    for (header_tag, metrics_tag) in [(Tag::HHEA, Tag::HMTX), (Tag(*b"vhea"), Tag(*b"vmtx"))] {
+        if let (Some(header), Some(metrics)) = (table(&header_tag.0), table(&metrics_tag.0)) {
+            let (header, metrics) = subset_metrics(header, metrics, num_glyphs, &old_ids)?;
+            tables.push((header_tag, header));
+            tables.push((metrics_tag, metrics));
+        }
+    }
+    if let Some(post) = table(b"post").filter(|post| post.len() >= 32) {
+        let mut new_post = post[..32].to_vec();
+        new_post[..4].copy_from_slice(&0x00030000u32.to_be_bytes());
+        tables.push((Tag(*b"post"), new_post));
+    }
+    if let Some(os2) = table(b"OS/2") {
+        let mut new_os2 = os2.to_vec();
+        if let (Some(first), Some(last), Some(range)) =
+            (mapping.keys().next(), mapping.keys().next_back(), new_os2.get_mut(64..68))
+        {
+            range[..2].copy_from_slice(&(*first.min(&0xFFFF) as u16).to_be_bytes());
+            range[2..].copy_from_slice(&(*last.min(&0xFFFF) as u16).to_be_bytes());
+        }
+        tables.push((Tag(*b"OS/2"), new_os2));
+    }
+    for tag in COPIED_TABLES {
+        if let Some(data) = table(tag) {
+            tables.push((Tag(*tag), data.to_vec()));
+        }
+    }
+
+    tables.sort_by_key(|(tag, _)| *tag);
+    let tables: Vec<_> = tables.iter().map(|(tag, data)| (*tag, &data[..])).collect();
+    Ok(write_sfnt(sfnt.flavor, &tables))
+}
+
+/// The code points a single font maps to glyphs, in ascending order.
+pub(super) fn mapped_codepoints(data: &[u8], font: &FontFile) -> Result<Vec<u32>, Error> {
+    let FontFile::Single(sfnt) = font else {
+        return Err(Error::Subset("font collections are not supported"));
+    };
+    let cmap = sfnt
+        .tables
+        .iter()
+        .find(|t| t.tag.0 == *b"cmap")
+        .ok_or(Error::Subset("cmap table is required"))?;
+    let cmap = &data[cmap.offset as usize..][..cmap.length as usize];
+    Ok(parse_cmap(cmap, &[0..=MAX_CODEPOINT])?.into_keys().collect())
+}
+
+/// Subset a `hmtx` or `vmtx` table, returning it along with its `hhea` or `vhea` header with the
+/// number of long metrics updated.
+///
+/// Trailing glyphs sharing the last advance are stored as side bearings only.
+fn subset_metrics(
+    header: &[u8],
+    metrics: &[u8],
+    num_glyphs: usize,
+    old_ids: &[u16],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let num_long_metrics = read_u16(header, 34, "metrics header")? as usize;
+    if !(1..=num_glyphs).contains(&num_long_metrics) {
+        return Err(Error::Subset("invalid number of long metrics"));
+    }
+    let last_advance = read_u16(metrics, 4 * (num_long_metrics - 1), "metrics table")?;
+    let metric = |glyph_id: usize| -> Result<(u16, u16), Error> {
+        if glyph_id < num_long_metrics {
+            Ok((
+                read_u16(metrics, 4 * glyph_id, "metrics table")?,
+                read_u16(metrics, 4 * glyph_id + 2, "metrics table")?,
+            ))
+        } else {
+            let pos = 4 * num_long_metrics + 2 * (glyph_id - num_long_metrics);
+            Ok((last_advance, read_u16(metrics, pos, "metrics table")?))
+        }
+    };
+    let kept = old_ids
+        .iter()
+        .map(|&glyph_id| metric(glyph_id as usize))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut num_long_metrics = kept.len();
+    while num_long_metrics > 1 && kept[num_long_metrics - 1].0 == kept[num_long_metrics - 2].0 {
+        num_long_metrics -= 1;
+    }
+    let mut new_metrics = Vec::with_capacity(4 * num_long_metrics + 2 * kept.len());
+    for (i, (advance, side_bearing)) in kept.iter().enumerate() {
+        if i < num_long_metrics {
+            new_metrics.extend_from_slice(&advance.to_be_bytes());
+        }
+        new_metrics.extend_from_slice(&side_bearing.to_be_bytes());
+    }
+    let mut new_header = header.to_vec();
+    new_header[34..36].copy_from_slice(&(num_long_metrics as u16).to_be_bytes());
+    Ok((new_header, new_metrics))
+}
+
+/// Read the code point to glyph mapping of the code points in `unicodes` from the preferred
+/// Unicode subtable of `cmap`, which must be in format 4 or 12.
+///
+/// Only the intersection of each segment or group with `unicodes` is visited, so that a subtable
+/// mapping huge ranges costs no more than the code points asked for.
+fn parse_cmap(cmap: &[u8], unicodes: &[RangeInclusive<u32>]) -> Result<BTreeMap<u32, u16>, Error> {
+    // Full repertoire subtables first, then BMP-only ones
+    const PREFERENCE: [(u16, u16); 6] = [(3, 10), (0, 6), (0, 4), (3, 1), (0, 3), (0, 2)];
+
+    let num_tables = read_u16(cmap, 2, "cmap table")? as usize;
+    let mut best: Option<(usize, usize)> = None;
+    for i in 0..num_tables {
+        let record = 4 + 8 * i;
+        let platform = read_u16(cmap, record, "cmap table")?;
+        let encoding = read_u16(cmap, record + 2, "cmap table")?;
+        let offset = read_u32(cmap, record + 4, "cmap table")? as usize;
+        let Some(rank) = PREFERENCE.iter().position(|&p| p == (platform, encoding)) else {
+            continue;
+        };
+        let format = read_u16(cmap, offset, "cmap table")?;
+        if (format == 4 || format == 12) && best.is_none_or(|(best_rank, _)| rank < best_rank) {
+            best = Some((rank, offset));
+        }
+    }
+    let Some((_, offset)) = best else {
+        return Err(Error::Subset("no Unicode cmap subtable in format 4 or 12"));
+    };
+
+    let requested = |start: u32, end: u32| {
+        unicodes.iter().filter_map(move |range| {
+            let (start, end) = (start.max(*range.start()), end.min(*range.end()));
+            (start <= end).then_some(start..=end)
+        })
+    };
+
+    let subtable = &cmap[offset..];
+    let mut mapping = BTreeMap::new();
+    if read_u16(subtable, 0, "cmap subtable")? == 4 {
+        let seg_count = read_u16(subtable, 6, "cmap subtable")? as usize / 2;
+        let (end_codes, start_codes) = (14, 16 + 2 * seg_count);
+        let (id_deltas, id_range_offsets) = (16 + 4 * seg_count, 16 + 6 * seg_count);
+        let mut previous_end = None;
+        for segment in 0..seg_count {
+            let end = read_u16(subtable, end_codes + 2 * segment, "cmap subtable")?;
+            let start = read_u16(subtable, start_codes + 2 * segment, "cmap subtable")?;
+            if start > end {
+                continue;
+            }
+            if previous_end.is_some_and(|previous_end| start <= previous_end) {
+                return Err(Error::Subset("cmap segments are unsorted or overlap"));
+            }
+            previous_end = Some(end);
+            let delta = read_u16(subtable, id_deltas + 2 * segment, "cmap subtable")?;
+            let range_offset_pos = id_range_offsets + 2 * segment;
+            let range_offset = read_u16(subtable, range_offset_pos, "cmap subtable")? as usize;
+            let codepoints = requested(u32::from(start), u32::from(end.min(0xFFFE)));
+            for codepoint in codepoints.flatten().map(|codepoint| codepoint as u16) {
+                let glyph_id = if range_offset == 0 {
+                    codepoint.wrapping_add(delta)
+                } else {
+                    let pos = range_offset_pos + range_offset + 2 * (codepoint - start) as usize;
+                    match read_u16(subtable, pos, "cmap subtable")? {
+                        0 => 0,
+                        glyph_id => glyph_id.wrapping_add(delta),
+                    }
+                };
+                if glyph_id != 0 {
+                    mapping.insert(u32::from(codepoint), glyph_id);
+                }
+            }
+        }
+    } else {
+        let num_groups = read_u32(subtable, 12, "cmap subtable")? as usize;
+        let mut previous_end = None;
+        for group in 0..num_groups {
+            let record = 16 + 12 * group;
+            let start = read_u32(subtable, record, "cmap subtable")?;
+            let end = read_u32(subtable, record + 4, "cmap subtable")?;
+            let start_glyph_id = read_u32(subtable, record + 8, "cmap subtable")?;
+            if start > end || previous_end.is_some_and(|previous_end| start <= previous_end) {
+                return Err(Error::Subset("cmap groups are unsorted or overlap"));
+            }
+            previous_end = Some(end);
+            // Code points past Unicode or mapping to glyph IDs past 65535 are ignored
+            let Some(last_glyph_offset) = 0xFFFF_u32.checked_sub(start_glyph_id) else {
+                continue;
+            };
+            let end = end.min(MAX_CODEPOINT).min(start.saturating_add(last_glyph_offset));
+            for codepoint in requested(start, end).flatten() {
+                match (start_glyph_id + (codepoint - start)) as u16 {
+                    0 => {}
+                    glyph_id => {
+                        mapping.insert(codepoint, glyph_id);
+                    }
+                }
+            }
+        }
+    }
+    Ok(mapping)
+}
+
+/// Build a `cmap` table with a Windows BMP subtable in format 4 and, when there are code points
+/// beyond the BMP or they do not fit in format 4, a Windows full repertoire subtable in format 12.
+fn build_cmap(mapping: &BTreeMap<u32, u16>) -> Vec<u8> {
+    let format4 = build_format4(mapping);
+    let format12 = (format4.is_none() || mapping.keys().next_back() > Some(&0xFFFF))
+        .then(|| build_format12(mapping));
+    let subtables: Vec<_> = [((3u16, 1u16), format4), ((3, 10), format12)]
+        .into_iter()
+        .filter_map(|(id, data)| Some((id, data?)))
+        .collect();
+
+    let mut cmap = Vec::new();
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    cmap.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + 8 * subtables.len();
+    for ((platform, encoding), data) in &subtables {
+        cmap.extend_from_slice(&platform.to_be_bytes());
+        cmap.extend_from_slice(&encoding.to_be_bytes());
+        cmap.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += data.len();
+    }
+    for (_, data) in &subtables {
+        cmap.extend_from_slice(data);
+    }
+    cmap
+}
+
+/// Build a format 4 subtable for the BMP code points, or `None` if it would exceed the 64 KiB
+/// length limit.
+///
+/// Each run of consecutive code points becomes a segment, mapped through `idDelta` when its glyph
+/// IDs are consecutive too and through `glyphIdArray` otherwise.
+fn build_format4(mapping: &BTreeMap<u32, u16>) -> Option<Vec<u8>> {
+    // (start, end, idDelta, glyph IDs for glyphIdArray)
+    let mut segments: Vec<(u16, u16, u16, Vec<u16>)> = Vec::new();
+    let mut runs: Vec<Vec<(u16, u16)>> = Vec::new();
+    for (&codepoint, &glyph_id) in mapping.range(..0xFFFF) {
+        let codepoint = codepoint as u16;
+        match runs.last_mut() {
+            Some(run) if run.last().is_some_and(|&(last, _)| last + 1 == codepoint) => {
+                run.push((codepoint, glyph_id));
+            }
+            _ => runs.push(vec![(codepoint, glyph_id)]),
+        }
+    }
+    for run in runs {
+        let (start, first_glyph_id) = run[0];
+        let end = run[run.len() - 1].0;
+        let delta = first_glyph_id.wrapping_sub(start);
+        if run
+            .iter()
+            .all(|&(codepoint, glyph_id)| glyph_id.wrapping_sub(codepoint) == delta)
+        {
+            segments.push((start, end, delta, Vec::new()));
+        } else {
+            segments.push((start, end, 0, run.iter().map(|&(_, glyph_id)| glyph_id).collect()));
+        }
+    }
+    segments.push((0xFFFF, 0xFFFF, 1, Vec::new()));
+
+    let seg_count = segments.len();
+    let glyph_id_count: usize = segments.iter().map(|(.., glyph_ids)| glyph_ids.len()).sum();
+    let length = 16 + 8 * seg_count + 2 * glyph_id_count;
+    if length > usize::from(u16::MAX) {
+        return None;
+    }
+
+    let entry_selector = seg_count.ilog2();
+    let search_range = 2 << entry_selector;
+    let mut data = Vec::with_capacity(length);
+    for value in [4, length, 0, 2 * seg_count, search_range, entry_selector as usize] {
+        data.extend_from_slice(&(value as u16).to_be_bytes());
+    }
+    data.extend_from_slice(&((2 * seg_count - search_range) as u16).to_be_bytes());
+    for (_, end, ..) in &segments {
+        data.extend_from_slice(&end.to_be_bytes());
+    }
+    data.extend_from_slice(&0u16.to_be_bytes());
+    for (start, ..) in &segments {
+        data.extend_from_slice(&start.to_be_bytes());
+    }
+    for (_, _, delta, _) in &segments {
+        data.extend_from_slice(&delta.to_be_bytes());
+    }
+    // idRangeOffset is relative to its own position in the idRangeOffset array
+    let mut array_index = 0;
+    for (segment, (.., glyph_ids)) in segments.iter().enumerate() {
+        let range_offset =
+            if glyph_ids.is_empty() { 0 } else { 2 * (seg_count - segment + array_index) };
+        data.extend_from_slice(&(range_offset as u16).to_be_bytes());
+        array_index += glyph_ids.len();
+    }
+    for (.., glyph_ids) in &segments {
+        for glyph_id in glyph_ids {
+            data.extend_from_slice(&glyph_id.to_be_bytes());
+        }
+    }
+    Some(data)
+}
+
+/// Build a format 12 subtable with a group for each run of consecutive code points mapping to
+/// consecutive glyph IDs.
+fn build_format12(mapping: &BTreeMap<u32, u16>) -> Vec<u8> {
+    // (startCharCode, endCharCode, startGlyphID)
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (&codepoint, &glyph_id) in mapping {
+        let glyph_id = u32::from(glyph_id);
+        match groups.last_mut() {
+            Some((start, end, start_glyph_id))
+                if *end + 1 == codepoint && *start_glyph_id + (codepoint - *start) == glyph_id =>
+            {
+                *end = codepoint;
+            }
+            _ => groups.push((codepoint, codepoint, glyph_id)),
+        }
+    }
+
+    let mut data = Vec::with_capacity(16 + 12 * groups.len());
+    data.extend_from_slice(&12u16.to_be_bytes());
+    data.extend_from_slice(&0u16.to_be_bytes());
+    data.extend_from_slice(&((16 + 12 * groups.len()) as u32).to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+    for (start, end, start_glyph_id) in groups {
+        data.extend_from_slice(&start.to_be_bytes());
+        data.extend_from_slice(&end.to_be_bytes());
+        data.extend_from_slice(&start_glyph_id.to_be_bytes());
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read, path::PathBuf};
+
+    use super::*;
+
+    fn read_fixture(name: &str) -> Vec<u8> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        read(path).expect("Failed to read test font")
+    }
+
+    fn table<'a>(data: &'a [u8], font: &FontFile, tag: &[u8; 4]) -> &'a [u8] {
+        let table = font.fonts()[0].tables.iter().find(|t| t.tag.0 == *tag).unwrap();
+        &data[table.offset as usize..][..table.length as usize]
+    }
+
+    #[test]
+    fn test_cmap_roundtrip() {
+        let mapping: BTreeMap<u32, u16> = [
+            (0x20, 1),
+            (0x21, 2),
+            (0x22, 3),
+            (0x41, 10),
+            (0x42, 5),
+            (0x43, 7),
+            (0xFFFE, 8),
+            (0x1F600, 9),
+        ]
+        .into();
+        let cmap = build_cmap(&mapping);
+        assert_eq!(parse_cmap(&cmap, &[0..=MAX_CODEPOINT]).unwrap(), mapping);
+
+        // The format 4 subtable alone holds the BMP part of the mapping
+        let bmp: BTreeMap<u32, u16> = mapping.range(..0x10000).map(|(&c, &g)| (c, g)).collect();
+        let mut format4_only = cmap[..4].to_vec();
+        format4_only[3] = 1;
+        format4_only.extend_from_slice(&cmap[4..8]);
+        format4_only.extend_from_slice(&12u32.to_be_bytes());
+        format4_only.extend_from_slice(&build_format4(&mapping).unwrap());
+        assert_eq!(parse_cmap(&format4_only, &[0..=MAX_CODEPOINT]).unwrap(), bmp);
+    }
+
+    #[test]
+    fn test_cmap_format12_groups_are_validated() {
+        // A `cmap` table holding one Windows full repertoire subtable with `groups`
+        fn format12_cmap(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+            let mut cmap = [0, 0, 0, 1, 0, 3, 0, 10].to_vec();
+            cmap.extend_from_slice(&12u32.to_be_bytes());
+            cmap.extend_from_slice(&[0, 12, 0, 0]);
+            cmap.extend_from_slice(&((16 + 12 * groups.len()) as u32).to_be_bytes());
+            cmap.extend_from_slice(&0u32.to_be_bytes());
+            cmap.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+            for (start, end, start_glyph_id) in groups {
+                cmap.extend_from_slice(&start.to_be_bytes());
+                cmap.extend_from_slice(&end.to_be_bytes());
+                cmap.extend_from_slice(&start_glyph_id.to_be_bytes());
+            }
+            cmap
+        }
+
+        // Only the requested code points of a group spanning all of u32 are visited
+        let cmap = format12_cmap(&[(0, u32::MAX, 0)]);
+        let mapping = parse_cmap(&cmap, &[0x41..=0x42, 0x1F600..=0x1F600]).unwrap();
+        assert_eq!(mapping, [(0x41, 0x41), (0x42, 0x42)].into());
+        let mapping = parse_cmap(&cmap, &[0..=MAX_CODEPOINT]).unwrap();
+        assert_eq!(mapping.len(), 0xFFFF);
+        assert_eq!(mapping.last_key_value(), Some((&0xFFFF, &0xFFFF)));
+
+        // Code points past Unicode are dropped
+        let cmap = format12_cmap(&[(0x10FFFE, u32::MAX, 1)]);
+        let mapping = parse_cmap(&cmap, &[0..=u32::MAX]).unwrap();
+        assert_eq!(mapping, [(0x10FFFE, 1), (MAX_CODEPOINT, 2)].into());
+
+        for groups in [
+            &[(0x42, 0x41, 1)][..],
+            &[(0x41, 0x50, 1), (0x50, 0x60, 1)],
+            &[(0x41, 0x50, 1), (0x20, 0x30, 1)],
+            &[(0, u32::MAX, 1), (0, u32::MAX, 1)],
+        ] {
+            assert!(matches!(
+                parse_cmap(&format12_cmap(groups), &[0..=MAX_CODEPOINT]),
+                Err(Error::Subset("cmap groups are unsorted or overlap"))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_subset_keeps_requested_glyphs() {
+        let data = read_fixture("WarpnineSans-Regular.ttf");
+        let font = FontFile::try_from(&data[..]).unwrap();
+        let original_mapping =
+            parse_cmap(table(&data, &font, b"cmap"), &[0..=MAX_CODEPOINT]).unwrap();
+        let original_hmtx = table(&data, &font, b"hmtx");
+
+        let spec = SubsetSpec {
+            unicodes: vec![0x41..=0x43],
+            glyph_ids: vec![3..=3],
+        };
+        let subset_data = subset(&data, &font, &spec).unwrap();
+        let subset_font = FontFile::try_from(&subset_data[..]).unwrap();
+        subset_font.verify_checksums(&subset_data).unwrap();
+
+        let mapping =
+            parse_cmap(table(&subset_data, &subset_font, b"cmap"), &[0..=MAX_CODEPOINT]).unwrap();
+        assert_eq!(mapping.keys().copied().collect::<Vec<_>>(), [0x41, 0x42, 0x43]);
+
+        let maxp = table(&subset_data, &subset_font, b"maxp");
+        let num_glyphs = read_u16(maxp, 4, "maxp").unwrap() as usize;
+        assert!(num_glyphs >= 5, "notdef, glyph 3 and A-C must be kept");
+        assert!(mapping.values().all(|&glyph_id| (glyph_id as usize) < num_glyphs));
+
+        // Advance widths follow their glyphs to their new IDs
+        let hhea = table(&subset_data, &subset_font, b"hhea");
+        let hmtx = table(&subset_data, &subset_font, b"hmtx");
+        let num_long_metrics = read_u16(hhea, 34, "hhea").unwrap() as usize;
+        let advance = |hmtx: &[u8], num_long_metrics: usize, glyph_id: usize| {
+            read_u16(hmtx, 4 * glyph_id.min(num_long_metrics - 1), "hmtx").unwrap()
+        };
+        let original_long_metrics =
+            read_u16(table(&data, &font, b"hhea"), 34, "hhea").unwrap() as usize;
+        for (codepoint, &glyph_id) in &mapping {
+            let original_glyph_id = original_mapping[codepoint] as usize;
+            assert_eq!(
+                advance(hmtx, num_long_metrics, glyph_id as usize),
+                advance(original_hmtx, original_long_metrics, original_glyph_id)
+            );
+        }
+    }
+
+    #[test]
+    fn test_subset_rejects_collections() {
+        let data = read_fixture("WarpnineSans-Regular.ttf");
+        let font = FontFile::try_from(&data[..]).unwrap();
+        let FontFile::Single(sfnt) = font else { unreachable!() };
+        let collection = FontFile::Collection { version: 0x00010000, fonts: vec![sfnt] };
+        assert!(matches!(
+            subset(&data, &collection, &SubsetSpec::default()),
+            Err(Error::Subset(_))
+        ));
+    }
+}
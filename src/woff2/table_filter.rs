@@ -0,0 +1,29 @@
+use super::tag::Tag;
+
+/// Which tables of the input font to encode.
+///
+/// ```no_run
+/// use ttf2woff2::{EncodeOptions, TableFilter, encode_with_options};
+///
+/// let ttf_data = std::fs::read("font.ttf").unwrap();
+/// let filter = TableFilter::Drop(vec!["DSIG".parse().unwrap(), "LTSH".parse().unwrap()]);
+/// let options = EncodeOptions { table_filter: Some(filter), ..EncodeOptions::default() };
+/// let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableFilter {
+    /// Encode every table except these.
+    Drop(Vec<Tag>),
+    /// Encode only these tables.
+    Keep(Vec<Tag>),
+}
+
+impl TableFilter {
+    /// Whether a table with this tag is encoded.
+    pub fn keeps(&self, tag: Tag) -> bool {
+        match self {
+            Self::Drop(tags) => !tags.contains(&tag),
+            Self::Keep(tags) => tags.contains(&tag),
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use std::{fmt, str::FromStr};
+
+use crate::Error;
+
+/// A four-byte OpenType table tag, such as `glyf` or `OS/2`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub(super) struct Tag(pub(super) [u8; 4]);
+pub struct Tag(pub(crate) [u8; 4]);
 
 const KNOWN_TAGS: [[u8; 4]; 63] = [
     *b"cmap", *b"head", *b"hhea", *b"hmtx", *b"maxp", *b"name", *b"OS/2", *b"post", *b"cvt ",
@@ -12,36 +17,52 @@ const KNOWN_TAGS: [[u8; 4]; 63] = [
 ];
 
 impl Tag {
-    pub const GLYF: Self = Self(*b"glyf");
-    pub const LOCA: Self = Self(*b"loca");
-    pub const HEAD: Self = Self(*b"head");
-    pub const MAXP: Self = Self(*b"maxp");
+    pub(crate) const GLYF: Self = Self(*b"glyf");
+    pub(crate) const LOCA: Self = Self(*b"loca");
+    pub(crate) const HEAD: Self = Self(*b"head");
+    pub(crate) const MAXP: Self = Self(*b"maxp");
+    pub(crate) const HHEA: Self = Self(*b"hhea");
+    pub(crate) const HMTX: Self = Self(*b"hmtx");
 
-    pub fn known_index(&self) -> Option<u8> {
+    pub(crate) fn known_index(&self) -> Option<u8> {
         KNOWN_TAGS.iter().position(|t| t == &self.0).map(|i| i as u8)
     }
 
+    pub(crate) fn from_known_index(index: u8) -> Option<Self> {
+        KNOWN_TAGS.get(index as usize).map(|&t| Self(t))
+    }
+
     #[inline]
-    pub fn is_glyf(&self) -> bool {
+    pub(crate) fn is_glyf(&self) -> bool {
         *self == Self::GLYF
     }
 
     #[inline]
-    pub fn is_loca(&self) -> bool {
+    pub(crate) fn is_loca(&self) -> bool {
         *self == Self::LOCA
     }
 
     #[inline]
-    pub fn is_head(&self) -> bool {
+    pub(crate) fn is_head(&self) -> bool {
         *self == Self::HEAD
     }
 
     #[inline]
-    pub fn is_maxp(&self) -> bool {
+    pub(crate) fn is_maxp(&self) -> bool {
         *self == Self::MAXP
     }
 
-    pub fn to_flags(self, transform_version: u8) -> u8 {
+    #[inline]
+    pub(crate) fn is_hhea(&self) -> bool {
+        *self == Self::HHEA
+    }
+
+    #[inline]
+    pub(crate) fn is_hmtx(&self) -> bool {
+        *self == Self::HMTX
+    }
+
+    pub(crate) fn to_flags(self, transform_version: u8) -> u8 {
         match self.known_index() {
             Some(idx) => idx | (transform_version << 6),
             None => 63 | (transform_version << 6),
@@ -49,12 +70,39 @@ impl Tag {
     }
 }
 
+/// Tags made of printable ASCII are shown as text (`glyf`, `CFF `), anything else as hex.
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.iter().all(|b| (0x20..0x7F).contains(b)) {
+            self.0.iter().try_for_each(|&b| write!(f, "{}", b as char))
+        } else {
+            write!(f, "0x{:08X}", u32::from_be_bytes(self.0))
+        }
+    }
+}
+
 impl From<[u8; 4]> for Tag {
     fn from(value: [u8; 4]) -> Self {
         Self(value)
     }
 }
 
+/// Parses one to four printable ASCII characters, padding shorter tags with spaces as in
+/// `cvt `.
+impl FromStr for Tag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if !(1..=4).contains(&bytes.len()) || !bytes.iter().all(|b| (0x20..0x7F).contains(b)) {
+            return Err(Error::InvalidTag(s.to_string()));
+        }
+        let mut tag = *b"    ";
+        tag[..bytes.len()].copy_from_slice(bytes);
+        Ok(Self(tag))
+    }
+}
+
 impl AsRef<[u8; 4]> for Tag {
     fn as_ref(&self) -> &[u8; 4] {
         &self.0
@@ -74,10 +122,36 @@ mod tests {
         assert_eq!(Tag(*b"XXXX").known_index(), None);
     }
 
+    #[test]
+    fn test_from_known_index() {
+        assert_eq!(Tag::from_known_index(10), Some(Tag::GLYF));
+        assert_eq!(Tag::from_known_index(62), Some(Tag(*b"Sill")));
+        assert_eq!(Tag::from_known_index(63), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Tag::GLYF.to_string(), "glyf");
+        assert_eq!(Tag(*b"CFF ").to_string(), "CFF ");
+        assert_eq!(Tag([0, 1, 0, 0]).to_string(), "0x00010000");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("glyf".parse::<Tag>().unwrap(), Tag::GLYF);
+        assert_eq!("cvt".parse::<Tag>().unwrap(), Tag(*b"cvt "));
+        assert_eq!("OS/2".parse::<Tag>().unwrap(), Tag(*b"OS/2"));
+        assert!(matches!("".parse::<Tag>(), Err(Error::InvalidTag(_))));
+        assert!(matches!("glyph".parse::<Tag>(), Err(Error::InvalidTag(_))));
+        assert!(matches!("gl\u{e9}".parse::<Tag>(), Err(Error::InvalidTag(_))));
+    }
+
     #[test]
     fn test_is_methods() {
         assert!(Tag(*b"glyf").is_glyf());
         assert!(Tag(*b"loca").is_loca());
         assert!(!Tag(*b"head").is_glyf());
+        assert!(Tag(*b"hmtx").is_hmtx());
+        assert!(!Tag(*b"hhea").is_hmtx());
     }
 }
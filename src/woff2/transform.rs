@@ -3,11 +3,18 @@ use std::io::Cursor;
 use byteorder::{BigEndian, ReadBytesExt};
 
 use super::{
+    hooks::{Hooks, Progress},
+    report::GlyfStreams,
     triplet::{EncodedTriplet, TripletInput},
-    varint::encode_255_u_int16,
+    varint::{decode_255_u_int16, encode_255_u_int16},
 };
 use crate::{Error, Error::DataTooShort};
 
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+/// Number of glyphs transformed between progress reports
+const GLYPHS_PER_PROGRESS: usize = 256;
+
 /// WOFF2 transformed glyf table header (36 bytes)
 struct TransformedGlyfHeader {
     pub version: u16,      // 0x0000
@@ -41,6 +48,52 @@ impl From<&TransformedGlyfHeader> for [u8; 36] {
     }
 }
 
+impl TryFrom<&[u8]> for TransformedGlyfHeader {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let bytes = data
+            .get(..36)
+            .ok_or(DataTooShort { context: "transformed glyf header" })?;
+        let u16_at = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+        Ok(Self {
+            version: u16_at(0),
+            option_flags: u16_at(2),
+            num_glyphs: u16_at(4),
+            index_format: u16_at(6),
+            n_contour_stream_size: u32_at(8),
+            n_points_stream_size: u32_at(12),
+            flag_stream_size: u32_at(16),
+            glyph_stream_size: u32_at(20),
+            composite_stream_size: u32_at(24),
+            bbox_stream_size: u32_at(28),
+            instruction_stream_size: u32_at(32),
+        })
+    }
+}
+
+impl From<&TransformedGlyfHeader> for GlyfStreams {
+    fn from(header: &TransformedGlyfHeader) -> Self {
+        Self {
+            n_contour: header.n_contour_stream_size,
+            n_points: header.n_points_stream_size,
+            flag: header.flag_stream_size,
+            glyph: header.glyph_stream_size,
+            composite: header.composite_stream_size,
+            bbox: header.bbox_stream_size,
+            instruction: header.instruction_stream_size,
+        }
+    }
+}
+
+/// The stream sizes recorded in the header of a transformed `glyf` table.
+pub(super) fn glyf_streams(transformed_glyf: &[u8]) -> Result<GlyfStreams, Error> {
+    TransformedGlyfHeader::try_from(transformed_glyf).map(|header| GlyfStreams::from(&header))
+}
+
 /// Builder for transformed glyf data
 struct TransformedGlyf {
     pub n_contour_stream: Vec<u8>,
@@ -51,6 +104,8 @@ struct TransformedGlyf {
     pub bbox_bitmap: Vec<u8>,
     pub bbox_stream: Vec<u8>,
     pub instruction_stream: Vec<u8>,
+    /// `xMin` of every glyph, 0 for empty glyphs, as used by the `hmtx` transform
+    pub x_mins: Vec<i16>,
 }
 
 impl TransformedGlyf {
@@ -65,6 +120,7 @@ impl TransformedGlyf {
             bbox_bitmap: vec![0u8; bbox_bitmap_size],
             bbox_stream: Vec::with_capacity(num_glyphs as usize),
             instruction_stream: Vec::with_capacity(glyf_size / 4),
+            x_mins: Vec::with_capacity(num_glyphs as usize),
         }
     }
 
@@ -86,6 +142,7 @@ impl TransformedGlyf {
 
     fn push_empty(&mut self) {
         self.n_contour_stream.extend_from_slice(&0i16.to_be_bytes());
+        self.x_mins.push(0);
     }
 
     fn encode_simple(&mut self, glyph_id: u16, glyph: &SimpleGlyph) {
@@ -125,6 +182,7 @@ impl TransformedGlyf {
         if !bbox_matches {
             self.push_bbox(glyph_id, glyph.x_min, glyph.y_min, glyph.x_max, glyph.y_max);
         }
+        self.x_mins.push(glyph.x_min);
     }
 
     fn encode_composite(&mut self, glyph_id: u16, data: &[u8]) -> Result<(), Error> {
@@ -139,37 +197,7 @@ impl TransformedGlyf {
 
         self.n_contour_stream.extend_from_slice(&num_contours.to_be_bytes());
 
-        // Walk component records starting at offset 10.
-        let mut pos = 10usize;
-        let mut have_instructions = false;
-        loop {
-            // Need at least flags(2) + glyphIndex(2).
-            if pos + 4 > data.len() {
-                return Err(Error::InvalidGlyph("composite component truncated"));
-            }
-            let flags = u16::from_be_bytes([data[pos], data[pos + 1]]);
-            let args = if flags & 0x0001 != 0 { 4 } else { 2 }; // ARG_1_AND_2_ARE_WORDS
-            let xform = if flags & 0x0008 != 0 {
-                2 // WE_HAVE_A_SCALE
-            } else if flags & 0x0040 != 0 {
-                4 // WE_HAVE_AN_X_AND_Y_SCALE
-            } else if flags & 0x0080 != 0 {
-                8 // WE_HAVE_A_TWO_BY_TWO
-            } else {
-                0
-            };
-            let rec = 4 + args + xform; // flags(2) + glyphIndex(2) + args + xform
-            if flags & 0x0100 != 0 {
-                have_instructions = true; // WE_HAVE_INSTRUCTIONS
-            }
-            if pos + rec > data.len() {
-                return Err(Error::InvalidGlyph("composite component exceeds bounds"));
-            }
-            pos += rec;
-            if flags & 0x0020 == 0 {
-                break; // no MORE_COMPONENTS
-            }
-        }
+        let (pos, have_instructions) = scan_components(data, 10)?;
 
         // Component bytes only (no trailing instructionLength/instructions).
         self.composite_stream.extend_from_slice(&data[10..pos]);
@@ -191,10 +219,11 @@ impl TransformedGlyf {
         }
 
         self.push_bbox(glyph_id, x_min, y_min, x_max, y_max);
+        self.x_mins.push(x_min);
         Ok(())
     }
 
-    fn finish(self, index_format: u16) -> Vec<u8> {
+    fn finish(self, index_format: u16) -> TransformedGlyfTable {
         let header = TransformedGlyfHeader {
             version: 0,
             option_flags: 0,
@@ -230,7 +259,54 @@ impl TransformedGlyf {
         output.extend_from_slice(&self.bbox_stream);
         output.extend_from_slice(&self.instruction_stream);
 
-        output
+        TransformedGlyfTable { glyf: output, x_mins: self.x_mins }
+    }
+}
+
+/// Walk composite glyph component records starting at `start`.
+///
+/// Returns the offset just past the last component and whether the `WE_HAVE_INSTRUCTIONS` flag
+/// was set on any component.
+fn scan_components(data: &[u8], start: usize) -> Result<(usize, bool), Error> {
+    walk_components(data, start, |_| {})
+}
+
+/// Like [`scan_components`], calling `visit` with the offset of each component's `glyphIndex`.
+pub(super) fn walk_components(
+    data: &[u8],
+    start: usize,
+    mut visit: impl FnMut(usize),
+) -> Result<(usize, bool), Error> {
+    let mut pos = start;
+    let mut have_instructions = false;
+    loop {
+        // Need at least flags(2) + glyphIndex(2).
+        if pos + 4 > data.len() {
+            return Err(Error::InvalidGlyph("composite component truncated"));
+        }
+        let flags = u16::from_be_bytes([data[pos], data[pos + 1]]);
+        let args = if flags & 0x0001 != 0 { 4 } else { 2 }; // ARG_1_AND_2_ARE_WORDS
+        let xform = if flags & 0x0008 != 0 {
+            2 // WE_HAVE_A_SCALE
+        } else if flags & 0x0040 != 0 {
+            4 // WE_HAVE_AN_X_AND_Y_SCALE
+        } else if flags & 0x0080 != 0 {
+            8 // WE_HAVE_A_TWO_BY_TWO
+        } else {
+            0
+        };
+        let rec = 4 + args + xform; // flags(2) + glyphIndex(2) + args + xform
+        if flags & 0x0100 != 0 {
+            have_instructions = true; // WE_HAVE_INSTRUCTIONS
+        }
+        if pos + rec > data.len() {
+            return Err(Error::InvalidGlyph("composite component exceeds bounds"));
+        }
+        visit(pos + 2);
+        pos += rec;
+        if flags & 0x0020 == 0 {
+            return Ok((pos, have_instructions)); // no MORE_COMPONENTS
+        }
     }
 }
 
@@ -341,6 +417,70 @@ impl SimpleGlyph {
 
         (x_min, y_min, x_max, y_max)
     }
+
+    /// Append the glyph in standard `glyf` encoding, compressing flags with repeat counts and
+    /// using short coordinate forms where possible.
+    fn serialize(&self, overlap: bool, out: &mut Vec<u8>) {
+        fn push_coord(delta: i16, coords: &mut Vec<u8>, short_bit: u8, same_bit: u8) -> u8 {
+            if delta == 0 {
+                same_bit
+            } else if (-255..=255).contains(&delta) {
+                coords.push(delta.unsigned_abs() as u8);
+                short_bit | if delta > 0 { same_bit } else { 0 }
+            } else {
+                coords.extend_from_slice(&delta.to_be_bytes());
+                0
+            }
+        }
+
+        out.extend_from_slice(&self.num_contours.to_be_bytes());
+        out.extend_from_slice(&self.x_min.to_be_bytes());
+        out.extend_from_slice(&self.y_min.to_be_bytes());
+        out.extend_from_slice(&self.x_max.to_be_bytes());
+        out.extend_from_slice(&self.y_max.to_be_bytes());
+        for &end in &self.end_pts {
+            out.extend_from_slice(&end.to_be_bytes());
+        }
+        out.extend_from_slice(&(self.instructions.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.instructions);
+
+        let mut x_coords = Vec::with_capacity(self.points.len() * 2);
+        let mut y_coords = Vec::with_capacity(self.points.len() * 2);
+        let mut last_flag = None;
+        let mut repeat = 0u8;
+        let mut prev_x: i16 = 0;
+        let mut prev_y: i16 = 0;
+        for (i, &(x, y, on_curve)) in self.points.iter().enumerate() {
+            let mut flag = u8::from(on_curve);
+            if i == 0 && overlap {
+                flag |= OVERLAP_SIMPLE;
+            }
+            flag |= push_coord(x.wrapping_sub(prev_x), &mut x_coords, 0x02, 0x10);
+            flag |= push_coord(y.wrapping_sub(prev_y), &mut y_coords, 0x04, 0x20);
+            prev_x = x;
+            prev_y = y;
+
+            if last_flag == Some(flag) && repeat != 255 {
+                if let Some(previous) = out.last_mut() {
+                    *previous |= 0x08; // REPEAT_FLAG
+                }
+                repeat += 1;
+            } else {
+                if repeat != 0 {
+                    out.push(repeat);
+                }
+                out.push(flag);
+                repeat = 0;
+            }
+            last_flag = Some(flag);
+        }
+        if repeat != 0 {
+            out.push(repeat);
+        }
+
+        out.extend_from_slice(&x_coords);
+        out.extend_from_slice(&y_coords);
+    }
 }
 
 impl TryFrom<(&[u8], i16)> for SimpleGlyph {
@@ -413,15 +553,22 @@ impl TryFrom<(&[u8], i16)> for SimpleGlyph {
     }
 }
 
+/// A transformed `glyf` table, along with the `xMin` of every glyph.
+pub(super) struct TransformedGlyfTable {
+    pub glyf: Vec<u8>,
+    pub x_mins: Vec<i16>,
+}
+
 pub(super) struct GlyfContext<'a> {
     pub glyf: &'a [u8],
     pub loca: &'a [u8],
     pub head: &'a [u8],
     pub maxp: &'a [u8],
+    pub hooks: Hooks<'a>,
 }
 
 impl GlyfContext<'_> {
-    pub(super) fn transform(&self) -> Result<Vec<u8>, Error> {
+    pub(super) fn transform(&self) -> Result<TransformedGlyfTable, Error> {
         if self.maxp.len() < 6 {
             return Err(DataTooShort { context: "maxp table" });
         }
@@ -468,8 +615,12 @@ impl GlyfContext<'_> {
             Ok(if short_loca { raw * 2 } else { raw })
         };
 
+        let total = num_glyphs as usize;
         let mut start = read_offset(0)?;
-        for glyph_id in 0..num_glyphs as usize {
+        for glyph_id in 0..total {
+            if glyph_id % GLYPHS_PER_PROGRESS == 0 && glyph_id > 0 {
+                self.hooks.report(Progress::Glyphs { done: glyph_id, total })?;
+            }
             let end = read_offset(glyph_id + 1)?;
             let start_usize = start as usize;
             let end_usize = end as usize;
@@ -501,7 +652,337 @@ impl GlyfContext<'_> {
 
             start = end;
         }
+        self.hooks.report(Progress::Glyphs { done: total, total })?;
 
         Ok(streams.finish(index_format as u16))
     }
 }
+
+/// `glyf` and `loca` tables rebuilt from a transformed `glyf` table, along with the `xMin` of
+/// every glyph for reconstructing a transformed `hmtx` table.
+pub(super) struct ReconstructedGlyf {
+    pub glyf: Vec<u8>,
+    pub loca: Vec<u8>,
+    pub x_mins: Vec<i16>,
+}
+
+fn read_slice<'a>(
+    cursor: &mut Cursor<&'a [u8]>,
+    len: usize,
+    err: &'static str,
+) -> Result<&'a [u8], Error> {
+    let data: &'a [u8] = cursor.get_ref();
+    let start = cursor.position() as usize;
+    let slice = data.get(start..start + len).ok_or(Error::InvalidWoff2(err))?;
+    cursor.set_position((start + len) as u64);
+    Ok(slice)
+}
+
+fn bit_is_set(bitmap: &[u8], index: usize) -> bool {
+    bitmap
+        .get(index >> 3)
+        .is_some_and(|byte| byte & (0x80 >> (index & 7)) != 0)
+}
+
+impl TryFrom<&[u8]> for ReconstructedGlyf {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let header = TransformedGlyfHeader::try_from(data)?;
+        let num_glyphs = header.num_glyphs as usize;
+        let bitmap_size = ((num_glyphs + 31) >> 5) << 2;
+        let short_loca = match header.index_format {
+            0 => true,
+            1 => false,
+            _ => return Err(Error::InvalidWoff2("invalid indexFormat")),
+        };
+
+        let mut streams = Cursor::new(data);
+        streams.set_position(36);
+        let mut next_stream = |size: u32| {
+            read_slice(&mut streams, size as usize, "transformed glyf stream exceeds table")
+        };
+        let n_contour_stream = next_stream(header.n_contour_stream_size)?;
+        let n_points_stream = next_stream(header.n_points_stream_size)?;
+        let flag_stream = next_stream(header.flag_stream_size)?;
+        let glyph_stream = next_stream(header.glyph_stream_size)?;
+        let composite_stream = next_stream(header.composite_stream_size)?;
+        let bbox_stream = next_stream(header.bbox_stream_size)?;
+        let instruction_stream = next_stream(header.instruction_stream_size)?;
+        let overlap_bitmap =
+            if header.option_flags & 1 != 0 { next_stream(bitmap_size as u32)? } else { &[] };
+
+        if n_contour_stream.len() != num_glyphs * 2 {
+            return Err(Error::InvalidWoff2("nContour stream size mismatch"));
+        }
+        if bbox_stream.len() < bitmap_size {
+            return Err(Error::InvalidWoff2("bbox stream shorter than bitmap"));
+        }
+        let (bbox_bitmap, bbox_values) = bbox_stream.split_at(bitmap_size);
+
+        let mut n_contours = Cursor::new(n_contour_stream);
+        let mut n_points = Cursor::new(n_points_stream);
+        let mut flags = Cursor::new(flag_stream);
+        let mut glyphs = Cursor::new(glyph_stream);
+        let mut composites = Cursor::new(composite_stream);
+        let mut bboxes = Cursor::new(bbox_values);
+        let mut instructions = Cursor::new(instruction_stream);
+
+        let mut read_bbox = || -> Result<[i16; 4], Error> {
+            let mut bbox = [0i16; 4];
+            for value in &mut bbox {
+                *value = bboxes
+                    .read_i16::<BigEndian>()
+                    .map_err(|_| Error::InvalidWoff2("bbox stream truncated"))?;
+            }
+            Ok(bbox)
+        };
+
+        let mut glyf = Vec::with_capacity(data.len() * 2);
+        let mut offsets = Vec::with_capacity(num_glyphs + 1);
+        let mut x_mins = Vec::with_capacity(num_glyphs);
+        for glyph_id in 0..num_glyphs {
+            offsets.push(glyf.len());
+            let num_contours = n_contours
+                .read_i16::<BigEndian>()
+                .map_err(|_| Error::InvalidWoff2("nContour stream truncated"))?;
+            let has_bbox = bit_is_set(bbox_bitmap, glyph_id);
+
+            if num_contours == 0 {
+                if has_bbox {
+                    return Err(Error::InvalidWoff2("empty glyph has explicit bbox"));
+                }
+                x_mins.push(0);
+                continue;
+            }
+
+            if num_contours > 0 {
+                let mut end_pts = Vec::with_capacity(num_contours as usize);
+                let mut total_points = 0u32;
+                for _ in 0..num_contours {
+                    total_points += u32::from(decode_255_u_int16(&mut n_points)?);
+                    let end = u16::try_from(total_points.wrapping_sub(1))
+                        .map_err(|_| Error::InvalidWoff2("too many points in glyph"))?;
+                    end_pts.push(end);
+                }
+
+                let mut points = Vec::with_capacity(total_points as usize);
+                let mut x: i16 = 0;
+                let mut y: i16 = 0;
+                for _ in 0..total_points {
+                    let flag = flags
+                        .read_u8()
+                        .map_err(|_| Error::InvalidWoff2("flag stream truncated"))?;
+                    let delta = TripletInput::decode(flag, &mut glyphs)?;
+                    x = x.wrapping_add(delta.dx);
+                    y = y.wrapping_add(delta.dy);
+                    points.push((x, y, delta.on_curve));
+                }
+
+                let instruction_length = decode_255_u_int16(&mut glyphs)? as usize;
+                let glyph_instructions = read_slice(
+                    &mut instructions,
+                    instruction_length,
+                    "instruction stream truncated",
+                )?;
+
+                let mut glyph = SimpleGlyph {
+                    num_contours,
+                    x_min: 0,
+                    y_min: 0,
+                    x_max: 0,
+                    y_max: 0,
+                    end_pts,
+                    instructions: glyph_instructions.to_vec(),
+                    points,
+                };
+                let [x_min, y_min, x_max, y_max] = if has_bbox {
+                    read_bbox()?
+                } else {
+                    let (x_min, y_min, x_max, y_max) = glyph.compute_bbox();
+                    [x_min, y_min, x_max, y_max]
+                };
+                (glyph.x_min, glyph.y_min, glyph.x_max, glyph.y_max) = (x_min, y_min, x_max, y_max);
+                x_mins.push(x_min);
+                glyph.serialize(bit_is_set(overlap_bitmap, glyph_id), &mut glyf);
+            } else {
+                if !has_bbox {
+                    return Err(Error::InvalidWoff2("composite glyph without explicit bbox"));
+                }
+                let start = composites.position() as usize;
+                let (end, have_instructions) = scan_components(composite_stream, start)?;
+                composites.set_position(end as u64);
+
+                glyf.extend_from_slice(&num_contours.to_be_bytes());
+                let bbox = read_bbox()?;
+                x_mins.push(bbox[0]);
+                for value in bbox {
+                    glyf.extend_from_slice(&value.to_be_bytes());
+                }
+                glyf.extend_from_slice(&composite_stream[start..end]);
+
+                if have_instructions {
+                    let instruction_length = decode_255_u_int16(&mut glyphs)?;
+                    let glyph_instructions = read_slice(
+                        &mut instructions,
+                        instruction_length as usize,
+                        "instruction stream truncated",
+                    )?;
+                    glyf.extend_from_slice(&instruction_length.to_be_bytes());
+                    glyf.extend_from_slice(glyph_instructions);
+                }
+            }
+
+            glyf.resize((glyf.len() + 3) & !3, 0);
+        }
+        offsets.push(glyf.len());
+
+        let loca = if short_loca {
+            if glyf.len() > 0x1FFFE {
+                return Err(Error::InvalidWoff2("glyf too large for short loca format"));
+            }
+            offsets
+                .iter()
+                .flat_map(|&offset| ((offset / 2) as u16).to_be_bytes())
+                .collect()
+        } else {
+            let last = *offsets.last().unwrap_or(&0);
+            if u32::try_from(last).is_err() {
+                return Err(Error::InvalidWoff2("glyf too large for long loca format"));
+            }
+            offsets
+                .iter()
+                .flat_map(|&offset| (offset as u32).to_be_bytes())
+                .collect()
+        };
+
+        Ok(Self { glyf, loca, x_mins })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read, path::PathBuf};
+
+    use super::*;
+    use crate::woff2::{sfnt::Sfnt, tag::Tag};
+
+    fn glyph_slices<'a>(glyf: &'a [u8], loca: &[u8], short_loca: bool) -> Vec<&'a [u8]> {
+        let offsets: Vec<usize> = if short_loca {
+            loca.chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]) as usize * 2)
+                .collect()
+        } else {
+            loca.chunks_exact(4)
+                .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]) as usize)
+                .collect()
+        };
+        offsets.windows(2).map(|w| &glyf[w[0]..w[1]]).collect()
+    }
+
+    #[test]
+    fn test_reconstruct_preserves_glyphs() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/WarpnineSans-Regular.ttf");
+        let font = read(path).unwrap();
+        let sfnt = Sfnt::try_from(&font[..]).unwrap();
+        let table = |tag: Tag| {
+            let t = sfnt.tables.iter().find(|t| t.tag == tag).unwrap();
+            &font[t.offset as usize..(t.offset + t.length) as usize]
+        };
+        let context = GlyfContext {
+            glyf: table(Tag::GLYF),
+            loca: table(Tag::LOCA),
+            head: table(Tag::HEAD),
+            maxp: table(Tag::MAXP),
+            hooks: Hooks::default(),
+        };
+        let short_loca = context.head[51] == 0;
+
+        let transformed = context.transform().unwrap();
+        let reconstructed = ReconstructedGlyf::try_from(transformed.glyf.as_slice()).unwrap();
+        assert_eq!(reconstructed.loca.len(), context.loca.len());
+        assert_eq!(reconstructed.x_mins, transformed.x_mins);
+
+        let original = glyph_slices(context.glyf, context.loca, short_loca);
+        let rebuilt = glyph_slices(&reconstructed.glyf, &reconstructed.loca, short_loca);
+        assert_eq!(original.len(), rebuilt.len());
+
+        for (glyph_id, (before, after)) in original.iter().zip(&rebuilt).enumerate() {
+            if before.is_empty() {
+                assert!(after.is_empty(), "glyph {glyph_id} should stay empty");
+                continue;
+            }
+            let num_contours = i16::from_be_bytes([before[0], before[1]]);
+            assert_eq!(&before[..10], &after[..10], "glyph {glyph_id} header differs");
+            if num_contours < 0 {
+                let (end, have_instructions) = scan_components(before, 10).unwrap();
+                let instructions_end = if have_instructions {
+                    end + 2 + u16::from_be_bytes([before[end], before[end + 1]]) as usize
+                } else {
+                    end
+                };
+                assert_eq!(&before[..instructions_end], &after[..instructions_end]);
+                continue;
+            }
+            let before = SimpleGlyph::try_from((*before, num_contours)).unwrap();
+            let after = SimpleGlyph::try_from((*after, num_contours)).unwrap();
+            assert_eq!(before.end_pts, after.end_pts, "glyph {glyph_id} contours differ");
+            assert_eq!(before.points, after.points, "glyph {glyph_id} points differ");
+            assert_eq!(before.instructions, after.instructions);
+        }
+    }
+
+    #[test]
+    fn test_serialize_roundtrips_simple_glyph() {
+        let glyph = SimpleGlyph {
+            num_contours: 2,
+            x_min: -300,
+            y_min: 0,
+            x_max: 700,
+            y_max: 700,
+            end_pts: vec![2, 6],
+            instructions: vec![0xB0, 0x01],
+            points: vec![
+                (0, 0, true),
+                (0, 0, true),
+                (-300, 700, false),
+                (700, 700, true),
+                (700, 10, true),
+                (690, 10, true),
+                (690, 0, false),
+            ],
+        };
+        let mut data = Vec::new();
+        glyph.serialize(true, &mut data);
+        let parsed = SimpleGlyph::try_from((data.as_slice(), 2)).unwrap();
+        assert_eq!(parsed.end_pts, glyph.end_pts);
+        assert_eq!(parsed.points, glyph.points);
+        assert_eq!(parsed.instructions, glyph.instructions);
+        assert_eq!(parsed.compute_bbox(), (-300, 0, 700, 700));
+        // OVERLAP_SIMPLE is kept on the first flag
+        assert_ne!(data[10 + 4 + 2 + 2] & OVERLAP_SIMPLE, 0);
+    }
+
+    #[test]
+    fn test_reconstruct_rejects_truncated_streams() {
+        let header = TransformedGlyfHeader {
+            version: 0,
+            option_flags: 0,
+            num_glyphs: 1,
+            index_format: 0,
+            n_contour_stream_size: 2,
+            n_points_stream_size: 0,
+            flag_stream_size: 0,
+            glyph_stream_size: 0,
+            composite_stream_size: 0,
+            bbox_stream_size: 4,
+            instruction_stream_size: 0,
+        };
+        let mut data = <[u8; 36]>::from(&header).to_vec();
+        data.extend_from_slice(&1i16.to_be_bytes());
+        data.extend_from_slice(&[0; 4]);
+        assert!(ReconstructedGlyf::try_from(data.as_slice()).is_err());
+        assert!(ReconstructedGlyf::try_from(&data[..30]).is_err());
+    }
+}
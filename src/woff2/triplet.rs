@@ -1,4 +1,7 @@
+use std::io::{Cursor, Read};
+
 use super::inline_bytes::InlineBytes;
+use crate::Error;
 
 /// Input for triplet encoding: (dx, dy, on_curve)
 pub(super) struct TripletInput {
@@ -106,6 +109,55 @@ impl From<TripletInput> for EncodedTriplet {
     }
 }
 
+impl TripletInput {
+    /// Decode a point delta from its flag byte and the following bytes of the glyph stream.
+    pub fn decode(flag: u8, glyph_stream: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+        fn with_sign(flag: u8, value: i32) -> i16 {
+            (if flag & 1 != 0 { value } else { -value }) as i16
+        }
+
+        let on_curve = flag & 0x80 == 0;
+        let flag = flag & 0x7F;
+        let data_len = match flag {
+            0..84 => 1,
+            84..120 => 2,
+            120..124 => 3,
+            _ => 4,
+        };
+        let mut buf = [0u8; 4];
+        glyph_stream
+            .read_exact(&mut buf[..data_len])
+            .map_err(|_| Error::InvalidWoff2("glyph stream truncated"))?;
+        let [b0, b1, b2, b3] = buf.map(i32::from);
+
+        let (dx, dy) = match flag {
+            0..10 => (0, with_sign(flag, (i32::from(flag & 14) << 7) + b0)),
+            10..20 => (with_sign(flag, (i32::from((flag - 10) & 14) << 7) + b0), 0),
+            20..84 => {
+                let b = i32::from(flag - 20);
+                (
+                    with_sign(flag, 1 + (b & 0x30) + (b0 >> 4)),
+                    with_sign(flag >> 1, 1 + ((b & 0x0C) << 2) + (b0 & 0x0F)),
+                )
+            }
+            84..120 => {
+                let b = i32::from(flag - 84);
+                (
+                    with_sign(flag, 1 + ((b / 12) << 8) + b0),
+                    with_sign(flag >> 1, 1 + (((b % 12) >> 2) << 8) + b1),
+                )
+            }
+            120..124 => (
+                with_sign(flag, (b0 << 4) + (b1 >> 4)),
+                with_sign(flag >> 1, ((b1 & 0x0F) << 8) + b2),
+            ),
+            _ => (with_sign(flag, (b0 << 8) + b1), with_sign(flag >> 1, (b2 << 8) + b3)),
+        };
+
+        Ok(Self { dx, dy, on_curve })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,6 +190,22 @@ mod tests {
         assert_eq!(encoded.data.as_slice().len(), 4);
     }
 
+    #[test]
+    fn test_decode_inverts_encode() {
+        let deltas = [0, 1, -1, 63, -64, 65, 255, 768, -769, 1279, 1280, 4095, 4096, 32767, -32767];
+        for &dx in &deltas {
+            for &dy in &deltas {
+                for on_curve in [true, false] {
+                    let encoded = EncodedTriplet::from(TripletInput { dx, dy, on_curve });
+                    let mut cursor = Cursor::new(encoded.data.as_slice());
+                    let decoded = TripletInput::decode(encoded.flag, &mut cursor).unwrap();
+                    assert_eq!((decoded.dx, decoded.dy, decoded.on_curve), (dx, dy, on_curve));
+                    assert_eq!(cursor.position() as usize, encoded.data.len());
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_off_curve() {
         let encoded = EncodedTriplet::from(TripletInput { dx: 0, dy: 100, on_curve: false });
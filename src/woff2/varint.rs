@@ -1,4 +1,9 @@
+use std::io::Cursor;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
 use super::inline_bytes::InlineBytes;
+use crate::Error;
 
 /// Encoded variable integer - up to 5 bytes for base128, 3 bytes for 255UInt16
 type EncodedInt = InlineBytes<5>;
@@ -54,6 +59,45 @@ pub(super) fn encode_255_u_int16(value: u16) -> EncodedInt {
     }
 }
 
+/// UIntBase128 decoding per WOFF2 spec.
+///
+/// Rejects leading zero bytes, sequences longer than 5 bytes, and values that overflow `u32`.
+pub(super) fn decode_base128(cursor: &mut Cursor<&[u8]>) -> Result<u32, Error> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = cursor
+            .read_u8()
+            .map_err(|_| Error::InvalidWoff2("truncated UIntBase128"))?;
+        if i == 0 && byte == 0x80 {
+            return Err(Error::InvalidWoff2("UIntBase128 with leading zeros"));
+        }
+        if value & 0xFE00_0000 != 0 {
+            return Err(Error::InvalidWoff2("UIntBase128 overflow"));
+        }
+        value = (value << 7) | u32::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::InvalidWoff2("UIntBase128 longer than 5 bytes"))
+}
+
+/// 255UInt16 decoding per WOFF2 spec.
+pub(super) fn decode_255_u_int16(cursor: &mut Cursor<&[u8]>) -> Result<u16, Error> {
+    const WORD_CODE: u8 = 253;
+    const ONE_MORE_BYTE_CODE2: u8 = 254;
+    const ONE_MORE_BYTE_CODE1: u8 = 255;
+
+    let err = |_| Error::InvalidWoff2("truncated 255UInt16");
+    let code = cursor.read_u8().map_err(err)?;
+    Ok(match code {
+        WORD_CODE => cursor.read_u16::<BigEndian>().map_err(err)?,
+        ONE_MORE_BYTE_CODE1 => u16::from(cursor.read_u8().map_err(err)?) + 253,
+        ONE_MORE_BYTE_CODE2 => u16::from(cursor.read_u8().map_err(err)?) + 506,
+        value => u16::from(value),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,6 +111,18 @@ mod tests {
         assert_eq!(encode_base128(128).as_slice(), &[0x81, 0x00]);
     }
 
+    #[test]
+    fn test_decode_base128() {
+        for value in [0, 63, 127, 128, 16383, 16384, u32::MAX] {
+            let encoded = encode_base128(value);
+            let mut cursor = Cursor::new(encoded.as_slice());
+            assert_eq!(decode_base128(&mut cursor).unwrap(), value);
+        }
+        assert!(decode_base128(&mut Cursor::new(&[0x80, 0x01][..])).is_err());
+        assert!(decode_base128(&mut Cursor::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x7F][..])).is_err());
+        assert!(decode_base128(&mut Cursor::new(&[0x81][..])).is_err());
+    }
+
     #[test]
     fn test_encode_255_u_int16() {
         assert_eq!(encode_255_u_int16(0).as_slice(), &[0]);
@@ -78,4 +134,14 @@ mod tests {
         assert_eq!(encode_255_u_int16(762).as_slice(), &[253, 0x02, 0xFA]);
         assert_eq!(encode_255_u_int16(0xFFFF).as_slice(), &[253, 0xFF, 0xFF]);
     }
+
+    #[test]
+    fn test_decode_255_u_int16() {
+        for value in [0, 252, 253, 505, 506, 761, 762, 0xFFFF] {
+            let encoded = encode_255_u_int16(value);
+            let mut cursor = Cursor::new(encoded.as_slice());
+            assert_eq!(decode_255_u_int16(&mut cursor).unwrap(), value);
+        }
+        assert!(decode_255_u_int16(&mut Cursor::new(&[253, 0x01][..])).is_err());
+    }
 }
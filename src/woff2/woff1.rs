@@ -0,0 +1,399 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    io::{Read, Write},
+};
+
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+
+use super::{
+    decoder::MAX_DECOMPRESSED_SIZE,
+    encoder::{EncodeOptions, Encoder, align4},
+    sfnt::{FontFile, table_checksum, write_sfnt},
+    tag::Tag,
+};
+use crate::{Error, Error::DataTooShort};
+
+pub(crate) const WOFF_SIGNATURE: u32 = 0x774F4646; // 'wOFF'
+
+const HEADER_SIZE: u32 = 44;
+const DIRECTORY_ENTRY_SIZE: u32 = 20;
+
+pub(crate) struct WoffHeader {
+    pub signature: u32,
+    pub flavor: u32,
+    pub length: u32,
+    pub num_tables: u16,
+    pub reserved: u16,
+    pub total_sfnt_size: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub meta_offset: u32,
+    pub meta_length: u32,
+    pub meta_orig_length: u32,
+    pub priv_offset: u32,
+    pub priv_length: u32,
+}
+
+impl TryFrom<&[u8]> for WoffHeader {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let bytes = data.get(..44).ok_or(DataTooShort { context: "WOFF header" })?;
+        let u16_at = |i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+        let header = Self {
+            signature: u32_at(0),
+            flavor: u32_at(4),
+            length: u32_at(8),
+            num_tables: u16_at(12),
+            reserved: u16_at(14),
+            total_sfnt_size: u32_at(16),
+            major_version: u16_at(20),
+            minor_version: u16_at(22),
+            meta_offset: u32_at(24),
+            meta_length: u32_at(28),
+            meta_orig_length: u32_at(32),
+            priv_offset: u32_at(36),
+            priv_length: u32_at(40),
+        };
+
+        if header.signature != WOFF_SIGNATURE {
+            return Err(Error::InvalidWoff("bad signature"));
+        }
+        if header.length as usize > data.len() {
+            return Err(DataTooShort { context: "WOFF file" });
+        }
+        if header.num_tables == 0 {
+            return Err(Error::InvalidWoff("no tables"));
+        }
+        Ok(header)
+    }
+}
+
+impl From<&WoffHeader> for [u8; 44] {
+    fn from(header: &WoffHeader) -> Self {
+        let mut bytes = [0u8; 44];
+        bytes[0..4].copy_from_slice(&header.signature.to_be_bytes());
+        bytes[4..8].copy_from_slice(&header.flavor.to_be_bytes());
+        bytes[8..12].copy_from_slice(&header.length.to_be_bytes());
+        bytes[12..14].copy_from_slice(&header.num_tables.to_be_bytes());
+        bytes[14..16].copy_from_slice(&header.reserved.to_be_bytes());
+        bytes[16..20].copy_from_slice(&header.total_sfnt_size.to_be_bytes());
+        bytes[20..22].copy_from_slice(&header.major_version.to_be_bytes());
+        bytes[22..24].copy_from_slice(&header.minor_version.to_be_bytes());
+        bytes[24..28].copy_from_slice(&header.meta_offset.to_be_bytes());
+        bytes[28..32].copy_from_slice(&header.meta_length.to_be_bytes());
+        bytes[32..36].copy_from_slice(&header.meta_orig_length.to_be_bytes());
+        bytes[36..40].copy_from_slice(&header.priv_offset.to_be_bytes());
+        bytes[40..44].copy_from_slice(&header.priv_length.to_be_bytes());
+        bytes
+    }
+}
+
+pub(crate) struct WoffTableEntry {
+    pub tag: Tag,
+    pub offset: u32,
+    pub comp_length: u32,
+    pub orig_length: u32,
+    pub orig_checksum: u32,
+}
+
+impl From<&[u8; 20]> for WoffTableEntry {
+    fn from(bytes: &[u8; 20]) -> Self {
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Self {
+            tag: Tag::from([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            offset: u32_at(4),
+            comp_length: u32_at(8),
+            orig_length: u32_at(12),
+            orig_checksum: u32_at(16),
+        }
+    }
+}
+
+impl From<&WoffTableEntry> for [u8; 20] {
+    fn from(entry: &WoffTableEntry) -> Self {
+        let mut bytes = [0u8; 20];
+        bytes[0..4].copy_from_slice(entry.tag.as_ref());
+        bytes[4..8].copy_from_slice(&entry.offset.to_be_bytes());
+        bytes[8..12].copy_from_slice(&entry.comp_length.to_be_bytes());
+        bytes[12..16].copy_from_slice(&entry.orig_length.to_be_bytes());
+        bytes[16..20].copy_from_slice(&entry.orig_checksum.to_be_bytes());
+        bytes
+    }
+}
+
+/// A WOFF 1.0 file unpacked into an SFNT font, along with its metadata and private data blocks.
+pub(super) struct WoffFont {
+    pub sfnt: Vec<u8>,
+    pub metadata: Option<String>,
+    pub private_data: Option<Vec<u8>>,
+}
+
+pub(super) fn is_woff1(data: &[u8]) -> bool {
+    data.starts_with(&WOFF_SIGNATURE.to_be_bytes())
+}
+
+fn zlib_compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = ZlibEncoder::new(Vec::with_capacity(data.len()), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Inflate a zlib stream that must decompress to exactly `orig_length` bytes.
+fn zlib_decompress(data: &[u8], orig_length: u32, err: &'static str) -> Result<Vec<u8>, Error> {
+    if u64::from(orig_length) > MAX_DECOMPRESSED_SIZE {
+        return Err(Error::InvalidWoff("original length exceeds 30 MB"));
+    }
+    // The declared length comes from the file, so only trust it as far as the compressed data
+    // could plausibly expand.
+    let mut inflated =
+        Vec::with_capacity(u64::from(orig_length).min(data.len() as u64 * 8) as usize);
+    // Reading one byte past the expected length catches streams that inflate to more
+    ZlibDecoder::new(data)
+        .take(u64::from(orig_length) + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|_| Error::InvalidWoff(err))?;
+    if inflated.len() != orig_length as usize {
+        return Err(Error::InvalidWoff(err));
+    }
+    Ok(inflated)
+}
+
+/// Unpack a WOFF 1.0 file, inflating every table and rebuilding the SFNT font around them.
+///
+/// Table checksums and `head.checkSumAdjustment` are recomputed for the rebuilt font. With
+/// `verify_checksums`, each inflated table is first checked against the checksum recorded in the
+/// WOFF table directory.
+pub(super) fn read_woff1(data: &[u8], verify_checksums: bool) -> Result<WoffFont, Error> {
+    let header = WoffHeader::try_from(data)?;
+    let data = &data[..header.length as usize];
+    let block = |offset: u32, length: u32, context: &'static str| {
+        data.get(offset as usize..offset as usize + length as usize)
+            .ok_or(DataTooShort { context })
+    };
+
+    let directory = block(44, 20 * u32::from(header.num_tables), "WOFF table directory")?;
+    let entries: Vec<_> = directory
+        .chunks_exact(20)
+        .map(|entry| WoffTableEntry::from(<&[u8; 20]>::try_from(entry).unwrap()))
+        .collect();
+    // Bound the rebuilt font by its declared size before inflating anything
+    let sfnt_size = 12
+        + 16 * u64::from(header.num_tables)
+        + entries
+            .iter()
+            .map(|entry| u64::from(entry.orig_length).next_multiple_of(4))
+            .sum::<u64>();
+    if u64::from(header.total_sfnt_size) > MAX_DECOMPRESSED_SIZE {
+        return Err(Error::InvalidWoff("totalSfntSize exceeds 30 MB"));
+    }
+    if sfnt_size > u64::from(header.total_sfnt_size) {
+        return Err(Error::InvalidWoff("tables exceed totalSfntSize"));
+    }
+    let tables = entries
+        .into_iter()
+        .map(|entry| {
+            let stored = block(entry.offset, entry.comp_length, "WOFF table data")?;
+            let table = match entry.comp_length.cmp(&entry.orig_length) {
+                Ordering::Less => Cow::Owned(zlib_decompress(
+                    stored,
+                    entry.orig_length,
+                    "table data does not inflate to its original length",
+                )?),
+                Ordering::Equal => Cow::Borrowed(stored),
+                Ordering::Greater => {
+                    return Err(Error::InvalidWoff("compressed table larger than original"));
+                }
+            };
+            if verify_checksums {
+                let actual = table_checksum(entry.tag, &table);
+                if actual != entry.orig_checksum {
+                    return Err(Error::ChecksumMismatch {
+                        tag: entry.tag,
+                        expected: entry.orig_checksum,
+                        actual,
+                    });
+                }
+            }
+            Ok((entry.tag, table))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let tables: Vec<_> = tables.iter().map(|(tag, table)| (*tag, &table[..])).collect();
+
+    let metadata = match header.meta_length {
+        0 => None,
+        length => {
+            let compressed = block(header.meta_offset, length, "WOFF metadata")?;
+            let xml = zlib_decompress(
+                compressed,
+                header.meta_orig_length,
+                "metadata does not inflate to its original length",
+            )?;
+            let xml =
+                String::from_utf8(xml).map_err(|_| Error::InvalidWoff("metadata is not UTF-8"))?;
+            Some(xml)
+        }
+    };
+    let private_data = match header.priv_length {
+        0 => None,
+        length => Some(block(header.priv_offset, length, "WOFF private data")?.to_vec()),
+    };
+
+    Ok(WoffFont {
+        sfnt: write_sfnt(header.flavor, &tables),
+        metadata,
+        private_data,
+    })
+}
+
+/// Encode a TTF or OTF font as WOFF 1.0, for user agents that do not support WOFF2.
+///
+/// Each table is zlib-compressed on its own and stored as-is when compression does not make it
+/// smaller, as the [WOFF 1.0 specification](https://www.w3.org/TR/WOFF/) requires. The extended
+/// metadata block is zlib-compressed too, and the private data block is stored as given.
+/// Subsetting, hinting removal, the table filter and checksum verification apply as for WOFF2;
+/// the Brotli and transform settings of `options` are ignored, since WOFF 1.0 has neither.
+///
+/// # Errors
+///
+/// Returns [`Error::CollectionNotSupported`] for TrueType collections, which WOFF 1.0 cannot
+/// hold, or an [`Error`] for the same reasons as [`encode`](super::encoder::encode).
+pub fn encode_woff1(ttf_data: &[u8], options: EncodeOptions) -> Result<Vec<u8>, Error> {
+    let encoder = Encoder::new(ttf_data, options)?;
+    let FontFile::Single(sfnt) = encoder.font() else {
+        return Err(Error::CollectionNotSupported);
+    };
+
+    // The table directory must be sorted by tag
+    let mut tables: Vec<_> = sfnt.tables.iter().collect();
+    tables.sort_by_key(|table| table.tag);
+    let stored = tables
+        .iter()
+        .map(|&table| {
+            let data = encoder.table_slice(table);
+            let compressed = zlib_compress(data)?;
+            Ok(if compressed.len() < data.len() {
+                Cow::Owned(compressed)
+            } else {
+                Cow::Borrowed(data)
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // Every table begins on a 4-byte boundary and is padded to the next one
+    let mut offset = HEADER_SIZE + DIRECTORY_ENTRY_SIZE * tables.len() as u32;
+    let entries: Vec<_> = tables
+        .iter()
+        .zip(&stored)
+        .map(|(table, data)| {
+            let entry = WoffTableEntry {
+                tag: table.tag,
+                offset,
+                comp_length: data.len() as u32,
+                orig_length: table.length,
+                orig_checksum: table.checksum,
+            };
+            offset = align4(offset + data.len() as u32);
+            entry
+        })
+        .collect();
+
+    let metadata = encoder
+        .options()
+        .metadata
+        .as_ref()
+        .map(|xml| Ok::<_, Error>((zlib_compress(xml.as_bytes())?, xml.len() as u32)))
+        .transpose()?;
+    let private_data = encoder.options().private_data.as_deref();
+    let mut length = offset;
+    let meta_offset = metadata.as_ref().map_or(0, |_| length);
+    if let Some((data, _)) = &metadata {
+        length = meta_offset + data.len() as u32;
+    }
+    // The private data block must begin on a 4-byte boundary and be the last block
+    let priv_offset = private_data.map_or(0, |_| align4(length));
+    if let Some(private_data) = private_data {
+        length = priv_offset + private_data.len() as u32;
+    }
+
+    let (major_version, minor_version) = tables
+        .iter()
+        .find(|table| table.tag.is_head())
+        .and_then(|&head| encoder.table_slice(head).get(4..8))
+        .map_or((0, 0), |revision| {
+            (
+                u16::from_be_bytes([revision[0], revision[1]]),
+                u16::from_be_bytes([revision[2], revision[3]]),
+            )
+        });
+    let header = WoffHeader {
+        signature: WOFF_SIGNATURE,
+        flavor: sfnt.flavor,
+        length,
+        num_tables: tables.len() as u16,
+        reserved: 0,
+        total_sfnt_size: 12
+            + 16 * tables.len() as u32
+            + tables.iter().map(|table| align4(table.length)).sum::<u32>(),
+        major_version,
+        minor_version,
+        meta_offset,
+        meta_length: metadata.as_ref().map_or(0, |(data, _)| data.len() as u32),
+        meta_orig_length: metadata.as_ref().map_or(0, |&(_, orig_length)| orig_length),
+        priv_offset,
+        priv_length: private_data.map_or(0, |data| data.len() as u32),
+    };
+
+    let mut woff_data = Vec::with_capacity(length as usize);
+    woff_data.extend_from_slice(&<[u8; 44]>::from(&header));
+    for entry in &entries {
+        woff_data.extend_from_slice(&<[u8; 20]>::from(entry));
+    }
+    for (entry, data) in entries.iter().zip(&stored) {
+        woff_data.resize(entry.offset as usize, 0);
+        woff_data.extend_from_slice(data);
+    }
+    woff_data.resize(offset as usize, 0);
+    if let Some((data, _)) = &metadata {
+        woff_data.extend_from_slice(data);
+    }
+    if let Some(private_data) = private_data {
+        woff_data.resize(priv_offset as usize, 0);
+        woff_data.extend_from_slice(private_data);
+    }
+    Ok(woff_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_layout() {
+        let header = WoffHeader {
+            signature: WOFF_SIGNATURE,
+            flavor: 0x00010000,
+            length: 1000,
+            num_tables: 3,
+            reserved: 0,
+            total_sfnt_size: 2000,
+            major_version: 1,
+            minor_version: 2,
+            meta_offset: 0,
+            meta_length: 0,
+            meta_orig_length: 0,
+            priv_offset: 996,
+            priv_length: 4,
+        };
+        let bytes = <[u8; 44]>::from(&header);
+        assert_eq!(&bytes[0..4], b"wOFF");
+        assert_eq!(&bytes[12..14], &[0, 3]);
+        assert_eq!(&bytes[20..24], &[0, 1, 0, 2]);
+        assert_eq!(&bytes[36..44], &[0, 0, 0x03, 0xE4, 0, 0, 0, 4]);
+    }
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "async")]
+
+use std::{fs::read, path::PathBuf};
+
+use tokio::runtime::Builder;
+use ttf2woff2::{BrotliQuality, EncodeOptions, Error, encode_async, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    }
+}
+
+#[test]
+fn test_encode_async_matches_sync() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let expected = encode_with_options(&ttf_data, options()).unwrap();
+
+    let runtime = Builder::new_current_thread().build().unwrap();
+    let woff2_data = runtime.block_on(encode_async(ttf_data, options())).unwrap();
+    assert_eq!(woff2_data, expected);
+}
+
+#[test]
+fn test_encode_async_error() {
+    let runtime = Builder::new_current_thread().build().unwrap();
+    let result = runtime.block_on(encode_async(b"not a font".to_vec(), options()));
+    assert!(matches!(result, Err(Error::UnsupportedFormat)));
+}
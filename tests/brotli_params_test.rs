@@ -0,0 +1,57 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{
+    BrotliBlockSize, BrotliMode, BrotliQuality, BrotliWindow, EncodeOptions, decode,
+    encode_with_options,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    }
+}
+
+#[test]
+fn test_defaults_match_reference_settings() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let explicit = EncodeOptions {
+        lgwin: BrotliWindow::try_from(22).unwrap(),
+        lgblock: BrotliBlockSize::AUTO,
+        mode: BrotliMode::Font,
+        ..options()
+    };
+    assert_eq!(
+        encode_with_options(&ttf_data, options()).unwrap(),
+        encode_with_options(&ttf_data, explicit).unwrap()
+    );
+}
+
+#[test]
+fn test_brotli_params_roundtrip() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let expected = decode(&encode_with_options(&ttf_data, options()).unwrap()).unwrap();
+
+    let settings = [
+        (BrotliWindow::MIN, 0, BrotliMode::Generic),
+        (BrotliWindow::MAX, BrotliBlockSize::MIN, BrotliMode::Text),
+        (BrotliWindow::MAX, BrotliBlockSize::MAX, BrotliMode::Font),
+    ];
+    for (lgwin, lgblock, mode) in settings {
+        let options = EncodeOptions {
+            lgwin: BrotliWindow::try_from(lgwin).unwrap(),
+            lgblock: BrotliBlockSize::try_from(lgblock).unwrap(),
+            mode,
+            ..options()
+        };
+        let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+        assert_eq!(decode(&woff2_data).unwrap(), expected, "lgwin {lgwin}, lgblock {lgblock}");
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    env,
+    fs::{self, File, read},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread::sleep,
+    time::{Duration, SystemTime},
+};
+
+use ttf2woff2::{BrotliQuality, EncodeCache, EncodeOptions, ProgressHook, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+/// An empty cache directory unique to the test.
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ttf2woff2-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    }
+}
+
+fn entries(cache: &EncodeCache) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(cache.dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[test]
+fn test_cache_hit_skips_encode() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let cache = EncodeCache::new(cache_dir("cache-hit"));
+    let reports = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&reports);
+    let counted = EncodeOptions {
+        progress: Some(ProgressHook::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })),
+        ..options()
+    };
+
+    let expected = encode_with_options(&ttf_data, options()).unwrap();
+    assert_eq!(cache.encode(&ttf_data, counted.clone()).unwrap(), expected);
+    let cold_reports = reports.load(Ordering::Relaxed);
+    assert!(cold_reports > 0);
+    assert_eq!(entries(&cache).len(), 1);
+
+    assert_eq!(cache.encode(&ttf_data, counted).unwrap(), expected);
+    assert_eq!(reports.load(Ordering::Relaxed), cold_reports);
+
+    // Other options are a separate entry
+    let hmtx = EncodeOptions { transform_hmtx: true, ..options() };
+    assert_eq!(
+        cache.encode(&ttf_data, hmtx.clone()).unwrap(),
+        encode_with_options(&ttf_data, hmtx).unwrap()
+    );
+    assert_eq!(entries(&cache).len(), 2);
+
+    fs::remove_dir_all(cache.dir()).unwrap();
+}
+
+#[test]
+fn test_cache_replaces_truncated_entry() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let cache = EncodeCache::new(cache_dir("cache-truncated"));
+    let expected = cache.encode(&ttf_data, options()).unwrap();
+
+    let entry = entries(&cache).remove(0);
+    fs::write(&entry, &expected[..expected.len() / 2]).unwrap();
+    assert_eq!(cache.encode(&ttf_data, options()).unwrap(), expected);
+    assert_eq!(fs::read(&entry).unwrap(), expected);
+
+    fs::remove_dir_all(cache.dir()).unwrap();
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let variants: Vec<_> = [5, 6, 7]
+        .into_iter()
+        .map(|quality| EncodeOptions { quality: BrotliQuality::from(quality), ..options() })
+        .collect();
+    let sizes: Vec<_> = variants
+        .iter()
+        .map(|options| encode_with_options(&ttf_data, options.clone()).unwrap().len() as u64)
+        .collect();
+
+    // Room for two entries but not three
+    let cache =
+        EncodeCache::new(cache_dir("cache-evict")).with_max_bytes(sizes.iter().sum::<u64>() - 1);
+    cache.encode(&ttf_data, variants[0].clone()).unwrap();
+    sleep(Duration::from_millis(20));
+    cache.encode(&ttf_data, variants[1].clone()).unwrap();
+    sleep(Duration::from_millis(20));
+    // A hit makes the first entry the most recently used one
+    cache.encode(&ttf_data, variants[0].clone()).unwrap();
+    sleep(Duration::from_millis(20));
+    cache.encode(&ttf_data, variants[2].clone()).unwrap();
+
+    let mut kept: Vec<_> = entries(&cache)
+        .iter()
+        .map(|entry| fs::metadata(entry).unwrap().len())
+        .collect();
+    kept.sort();
+    let mut expected = vec![sizes[0], sizes[2]];
+    expected.sort();
+    assert_eq!(kept, expected);
+
+    // Encodes larger than the cap are not stored
+    let tiny = EncodeCache::new(cache_dir("cache-tiny")).with_max_bytes(1);
+    tiny.encode(&ttf_data, options()).unwrap();
+    assert!(!tiny.dir().exists());
+
+    fs::remove_dir_all(cache.dir()).unwrap();
+}
+
+#[test]
+fn test_cache_errors_are_ignored() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let expected = encode_with_options(&ttf_data, options()).unwrap();
+
+    // A file in place of the directory can be neither read nor written
+    let path = cache_dir("cache-unusable");
+    fs::write(&path, b"not a directory").unwrap();
+    let cache = EncodeCache::new(&path);
+    assert_eq!(cache.encode(&ttf_data, options()).unwrap(), expected);
+    assert_eq!(cache.encode(&ttf_data, options()).unwrap(), expected);
+    assert_eq!(fs::read(&path).unwrap(), b"not a directory");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_removes_stale_temp_files() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let cache = EncodeCache::new(cache_dir("cache-stale"));
+    fs::create_dir_all(cache.dir()).unwrap();
+    let stale = cache.dir().join(".1-0.tmp");
+    let fresh = cache.dir().join(".1-1.tmp");
+    fs::write(&stale, b"crashed writer").unwrap();
+    fs::write(&fresh, b"active writer").unwrap();
+    File::options()
+        .append(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+        .unwrap();
+
+    cache.encode(&ttf_data, options()).unwrap();
+    assert!(!stale.exists());
+    assert!(fresh.exists());
+
+    fs::remove_dir_all(cache.dir()).unwrap();
+}
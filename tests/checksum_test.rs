@@ -0,0 +1,66 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{EncodeOptions, Error, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Returns the offset of the table with the given tag.
+fn table_offset(font: &[u8], tag: &[u8; 4]) -> usize {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    (0..num_tables)
+        .map(|i| 12 + 16 * i)
+        .find(|&record| &font[record..record + 4] == tag)
+        .map(|record| be_u32(font, record + 8) as usize)
+        .expect("table not found")
+}
+
+fn strict() -> EncodeOptions {
+    EncodeOptions { verify_checksums: true, ..EncodeOptions::default() }
+}
+
+#[test]
+fn test_strict_accepts_valid_font() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let strict_output = encode_with_options(&ttf_data, strict()).unwrap();
+    let output = encode_with_options(&ttf_data, EncodeOptions::default()).unwrap();
+    assert_eq!(strict_output, output);
+}
+
+#[test]
+fn test_strict_rejects_corrupted_table() {
+    let mut ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let offset = table_offset(&ttf_data, b"name");
+    ttf_data[offset + 6] ^= 0xFF;
+
+    // Checksums are ignored by default
+    encode_with_options(&ttf_data, EncodeOptions::default()).unwrap();
+
+    let err = encode_with_options(&ttf_data, strict()).unwrap_err();
+    let Error::ChecksumMismatch { tag, expected, actual } = err else {
+        panic!("expected a checksum mismatch, got {err:?}");
+    };
+    assert_eq!(tag.to_string(), "name");
+    assert_ne!(expected, actual);
+}
+
+#[test]
+fn test_strict_rejects_wrong_checksum_adjustment() {
+    let mut ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let offset = table_offset(&ttf_data, b"head");
+    ttf_data[offset + 8] ^= 0xFF;
+
+    let err = encode_with_options(&ttf_data, strict()).unwrap_err();
+    let Error::ChecksumMismatch { tag, .. } = err else {
+        panic!("expected a checksum mismatch, got {err:?}");
+    };
+    assert_eq!(tag.to_string(), "head");
+}
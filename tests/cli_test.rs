@@ -0,0 +1,281 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+use ttf2woff2::{BrotliQuality, encode};
+
+fn ttf2woff2(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ttf2woff2"))
+        .args(args)
+        .output()
+        .expect("Failed to run ttf2woff2")
+}
+
+/// Run ttf2woff2 with `stdin` piped in.
+fn ttf2woff2_piped(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ttf2woff2"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run ttf2woff2");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    fs::read(path).expect("Failed to read test font")
+}
+
+/// An empty directory unique to the test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ttf2woff2-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_info_rejects_huge_metadata_length() {
+    // One `name` table directory entry, then a one-byte metadata block declaring 4 GiB
+    let mut woff2_data = b"wOF2".to_vec();
+    woff2_data.extend_from_slice(&0x00010000u32.to_be_bytes());
+    woff2_data.extend_from_slice(&51u32.to_be_bytes());
+    woff2_data.extend_from_slice(&[0, 1, 0, 0]);
+    woff2_data.extend_from_slice(&[0; 12]);
+    woff2_data.extend_from_slice(&50u32.to_be_bytes());
+    woff2_data.extend_from_slice(&1u32.to_be_bytes());
+    woff2_data.extend_from_slice(&u32::MAX.to_be_bytes());
+    woff2_data.extend_from_slice(&[0; 8]);
+    woff2_data.extend_from_slice(&[0x05, 0x01, 0x00]);
+    let dir = temp_dir("cli-info");
+    let path = dir.join("forged.woff2");
+    fs::write(&path, &woff2_data).unwrap();
+
+    let output = ttf2woff2(&["info", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error reading"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_pipe_stdin_to_stdout() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let expected = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+
+    // `-` as the input writes to stdout by default, as does `-o -`
+    for args in [&["-q", "5", "-"][..], &["-q", "5", "-", "-o", "-"]] {
+        let output = ttf2woff2_piped(args, &ttf_data);
+        assert!(output.status.success());
+        assert_eq!(output.stdout, expected);
+    }
+
+    // A file input can be written to stdout too
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/WarpnineSans-Regular.ttf");
+    let output = ttf2woff2(&["-q", "5", path.to_str().unwrap(), "-o", "-"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+
+    // Input that is not a font fails without writing anything
+    let output = ttf2woff2_piped(&["-"], b"not a font");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+fn keys(value: &serde_json::Value) -> Vec<&str> {
+    value.as_object().unwrap().keys().map(String::as_str).collect()
+}
+
+#[test]
+fn test_json_output_schema() {
+    let dir = temp_dir("cli-json");
+    let font =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/WarpnineSans-Regular.ttf");
+    let bad = dir.join("bad.ttf");
+    fs::write(&bad, b"not a font").unwrap();
+
+    let output = ttf2woff2(&[
+        "-f",
+        "json",
+        "-q",
+        "5",
+        font.to_str().unwrap(),
+        bad.to_str().unwrap(),
+        "--out-dir",
+        dir.join("out").to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(
+        keys(&json),
+        [
+            "converted",
+            "elapsed_seconds",
+            "failed",
+            "files",
+            "jobs",
+            "lgblock",
+            "lgwin",
+            "mode",
+            "quality",
+            "stylesheets",
+            "threads"
+        ]
+    );
+    assert_eq!((json["converted"].as_u64(), json["failed"].as_u64()), (Some(1), Some(1)));
+    assert_eq!(json["quality"], 5);
+
+    let converted = &json["files"][0];
+    assert_eq!(
+        keys(converted),
+        [
+            "compression_percent",
+            "elapsed_seconds",
+            "error",
+            "input",
+            "input_size",
+            "optimized",
+            "output",
+            "output_size",
+            "ratio",
+            "report",
+            "tables"
+        ]
+    );
+    let tables = converted["tables"].as_array().unwrap();
+    assert_eq!(
+        keys(&tables[0]),
+        ["compressed_share", "orig_length", "stored_length", "tag", "transformed"]
+    );
+    // The shares split the Brotli stream, which is all of the file but its header and directory
+    let shares: u64 = tables
+        .iter()
+        .map(|table| table["compressed_share"].as_u64().unwrap())
+        .sum();
+    let output_size = converted["output_size"].as_u64().unwrap();
+    assert!(shares > output_size * 9 / 10 && shares < output_size);
+    assert!(converted["report"].is_null());
+
+    let failed = &json["files"][1];
+    assert_eq!(keys(failed), ["error", "input", "output"]);
+    assert_eq!(keys(&failed["error"]), ["message", "path", "stage"]);
+    assert_eq!(failed["error"]["stage"], "encode");
+
+    // The tables list the same shares as the report
+    let output =
+        ttf2woff2(&["-f", "json", "-q", "5", "--report", font.to_str().unwrap(), "-o", "-"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let report = &json["files"][0]["report"];
+    let tables = json["files"][0]["tables"].as_array().unwrap();
+    assert!(
+        tables
+            .iter()
+            .zip(report["tables"].as_array().unwrap())
+            .all(|(table, reported)| table["compressed_share"] == reported["compressed_share"])
+    );
+    assert_eq!(
+        keys(report),
+        [
+            "directory_length",
+            "length",
+            "metadata_length",
+            "private_data_length",
+            "tables",
+            "total_compressed_size",
+            "total_sfnt_size"
+        ]
+    );
+    assert_eq!(
+        keys(&report["tables"][0]),
+        [
+            "brotli_length",
+            "compressed_share",
+            "glyf_streams",
+            "orig_length",
+            "tag",
+            "transform_length"
+        ]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_woff_json_lists_tables() {
+    let dir = temp_dir("cli-woff-json");
+    let font =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/WarpnineSans-Regular.ttf");
+    let output_path = dir.join("font.woff");
+
+    let output = ttf2woff2(&[
+        "--woff",
+        "-f",
+        "json",
+        font.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tables = json["files"][0]["tables"].as_array().unwrap();
+
+    let woff_data = fs::read(&output_path).unwrap();
+    assert_eq!(tables.len(), usize::from(u16::from_be_bytes([woff_data[12], woff_data[13]])));
+    assert_eq!(tables[0]["tag"], "GDEF");
+    assert!(tables.iter().all(
+        |table| table["transformed"] == false && table["stored_length"] == table["orig_length"]
+    ));
+    // Each table is compressed on its own, so its share is its compLength
+    for (index, table) in tables.iter().enumerate() {
+        let entry = &woff_data[44 + 20 * index..];
+        let comp_length = u32::from_be_bytes(entry[8..12].try_into().unwrap());
+        assert_eq!(table["compressed_share"], comp_length);
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+
+    // `--format` is the summary format, and its help points to `--woff`
+    let help = String::from_utf8(ttf2woff2(&["--help"]).stdout).unwrap();
+    let format_help = help
+        .lines()
+        .skip_while(|line| !line.contains("--format"))
+        .nth(1)
+        .unwrap();
+    assert!(format_help.contains("use --woff to write WOFF 1.0"));
+}
+
+#[test]
+fn test_json_rejects_cache_dir() {
+    let dir = temp_dir("cli-json-cache");
+    let font =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/WarpnineSans-Regular.ttf");
+
+    let output = ttf2woff2(&[
+        "-f",
+        "json",
+        "--cache-dir",
+        dir.join("cache").to_str().unwrap(),
+        font.to_str().unwrap(),
+        "-o",
+        dir.join("font.woff2").to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["error"]["message"].as_str().unwrap().starts_with("--cache-dir"));
+    assert!(!dir.join("font.woff2").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,169 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{BrotliQuality, Woff2Info, decode, encode, encode_no_transform};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Returns `(tag, offset, data)` for every table record of the font directory at `start`.
+fn font_tables(file: &[u8], start: usize) -> Vec<([u8; 4], usize, Vec<u8>)> {
+    let num_tables = u16::from_be_bytes([file[start + 4], file[start + 5]]) as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = start + 12 + 16 * i;
+            let offset = be_u32(file, record + 8) as usize;
+            let length = be_u32(file, record + 12) as usize;
+            let tag = file[record..record + 4].try_into().unwrap();
+            (tag, offset, file[offset..offset + length].to_vec())
+        })
+        .collect()
+}
+
+fn ttc_font_offsets(ttc: &[u8]) -> Vec<usize> {
+    assert_eq!(&ttc[0..4], b"ttcf");
+    let num_fonts = be_u32(ttc, 8) as usize;
+    (0..num_fonts).map(|i| be_u32(ttc, 12 + 4 * i) as usize).collect()
+}
+
+/// Builds a TrueType collection. `fonts` lists `(tag, index into pool)` for each font, so a pool
+/// entry referenced by several fonts is stored once and shared.
+fn build_ttc(fonts: &[Vec<([u8; 4], usize)>], pool: &[Vec<u8>]) -> Vec<u8> {
+    let header_size = 12 + 4 * fonts.len();
+    let directories_size: usize = fonts.iter().map(|f| 12 + 16 * f.len()).sum();
+    let mut pool_offsets = Vec::with_capacity(pool.len());
+    let mut offset = header_size + directories_size;
+    for data in pool {
+        pool_offsets.push(offset);
+        offset += (data.len() + 3) & !3;
+    }
+
+    let mut ttc = b"ttcf".to_vec();
+    ttc.extend_from_slice(&0x00010000u32.to_be_bytes());
+    ttc.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+    let mut directory_offset = header_size;
+    for font in fonts {
+        ttc.extend_from_slice(&(directory_offset as u32).to_be_bytes());
+        directory_offset += 12 + 16 * font.len();
+    }
+    for font in fonts {
+        ttc.extend_from_slice(&0x00010000u32.to_be_bytes());
+        ttc.extend_from_slice(&(font.len() as u16).to_be_bytes());
+        ttc.extend_from_slice(&[0; 6]);
+        for &(tag, index) in font {
+            ttc.extend_from_slice(&tag);
+            ttc.extend_from_slice(&[0; 4]);
+            ttc.extend_from_slice(&(pool_offsets[index] as u32).to_be_bytes());
+            ttc.extend_from_slice(&(pool[index].len() as u32).to_be_bytes());
+        }
+    }
+    for data in pool {
+        ttc.extend_from_slice(data);
+        ttc.resize((ttc.len() + 3) & !3, 0);
+    }
+    ttc
+}
+
+/// A three-font collection: WarpnineSans, a copy of it that only differs in its `name` table,
+/// and Recursive, which has its own `glyf`/`loca` pair.
+fn collection() -> (Vec<u8>, usize) {
+    let mut pool = Vec::new();
+    let mut fonts = Vec::new();
+    for name in ["WarpnineSans-Regular.ttf", "Recursive_VF_1.085.ttf"] {
+        let ttf_data = read_fixture(name);
+        let mut font = Vec::new();
+        for (tag, _, data) in font_tables(&ttf_data, 0) {
+            font.push((tag, pool.len()));
+            pool.push(data);
+        }
+        fonts.push(font);
+    }
+
+    let mut variant = fonts[0].clone();
+    let name = variant.iter_mut().find(|(tag, _)| tag == b"name").unwrap();
+    let mut name_data = pool[name.1].clone();
+    name_data.extend_from_slice(b"variant");
+    name.1 = pool.len();
+    pool.push(name_data);
+    fonts.insert(1, variant);
+
+    (build_ttc(&fonts, &pool), pool.len())
+}
+
+#[test]
+fn test_collection_header() {
+    let (ttc, unique_tables) = collection();
+    let woff2_data = encode(&ttc, BrotliQuality::from(5)).unwrap();
+
+    assert_eq!(&woff2_data[0..4], b"wOF2");
+    assert_eq!(&woff2_data[4..8], b"ttcf");
+    let num_tables = u16::from_be_bytes([woff2_data[12], woff2_data[13]]) as usize;
+    assert_eq!(num_tables, unique_tables);
+    assert_eq!(be_u32(&woff2_data, 8) as usize, woff2_data.len());
+}
+
+#[test]
+fn test_collection_roundtrip_shares_tables() {
+    let (ttc, _) = collection();
+    let decoded = decode(&encode(&ttc, BrotliQuality::from(5)).unwrap()).unwrap();
+
+    let original_offsets = ttc_font_offsets(&ttc);
+    let decoded_offsets = ttc_font_offsets(&decoded);
+    assert_eq!(original_offsets.len(), decoded_offsets.len());
+
+    for (&original_start, &decoded_start) in original_offsets.iter().zip(&decoded_offsets) {
+        let original = font_tables(&ttc, original_start);
+        let roundtripped = font_tables(&decoded, decoded_start);
+        assert_eq!(original.len(), roundtripped.len());
+        for ((tag, _, data), (decoded_tag, _, decoded_data)) in original.iter().zip(&roundtripped) {
+            assert_eq!(tag, decoded_tag);
+            match tag {
+                b"glyf" => {}
+                b"loca" => assert_eq!(data.len(), decoded_data.len()),
+                b"head" => assert_eq!(&data[12..], &decoded_data[12..]),
+                _ => assert_eq!(data, decoded_data, "table {}", String::from_utf8_lossy(tag)),
+            }
+        }
+    }
+
+    // The first two fonts still share everything except `name`
+    let first = font_tables(&decoded, decoded_offsets[0]);
+    let second = font_tables(&decoded, decoded_offsets[1]);
+    for ((tag, offset, _), (_, second_offset, _)) in first.iter().zip(&second) {
+        assert_eq!(tag == b"name", offset != second_offset, "{}", String::from_utf8_lossy(tag));
+    }
+}
+
+#[test]
+fn test_collection_transforms_each_glyf() {
+    let (ttc, _) = collection();
+    let with_transform = encode(&ttc, BrotliQuality::from(5)).unwrap();
+    let without_transform = encode_no_transform(&ttc, BrotliQuality::from(5)).unwrap();
+    assert!(with_transform.len() < without_transform.len());
+}
+
+#[test]
+fn test_collection_info() {
+    let (ttc, unique_tables) = collection();
+    let woff2_data = encode(&ttc, BrotliQuality::from(5)).unwrap();
+    let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+
+    assert_eq!(info.flavor, u32::from_be_bytes(*b"ttcf"));
+    assert_eq!(info.tables.len(), unique_tables);
+    let collection = info.collection.unwrap();
+    assert_eq!(collection.version, 0x00010000);
+    assert_eq!(collection.fonts.len(), 3);
+
+    // The variant shares every table with the first font except `name`
+    let (first, variant) = (&collection.fonts[0].table_indices, &collection.fonts[1].table_indices);
+    let shared = first.iter().filter(|&index| variant.contains(index)).count();
+    assert_eq!(shared, first.len() - 1);
+    assert!(collection.fonts.iter().all(|font| font.flavor == 0x00010000));
+}
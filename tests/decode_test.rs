@@ -0,0 +1,267 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{
+    BrotliQuality, EncodeOptions, Error, decode, encode, encode_no_transform, encode_with_options,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Returns `(tag, checksum, data)` for every table record of a single-font SFNT.
+fn sfnt_tables(font: &[u8]) -> Vec<([u8; 4], u32, &[u8])> {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let tag = font[record..record + 4].try_into().unwrap();
+            let offset = be_u32(font, record + 8) as usize;
+            let length = be_u32(font, record + 12) as usize;
+            (tag, be_u32(font, record + 4), &font[offset..offset + length])
+        })
+        .collect()
+}
+
+fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> &'a [u8] {
+    sfnt_tables(font)
+        .into_iter()
+        .find(|(t, ..)| t == tag)
+        .map(|(_, _, data)| data)
+        .unwrap()
+}
+
+/// Asserts that every decoded table except `glyf`, `loca` and `head.checkSumAdjustment` is
+/// identical to the original, and that the loca table keeps its size.
+fn assert_same_font_data(original: &[u8], decoded: &[u8]) {
+    for (tag, _, data) in sfnt_tables(decoded) {
+        let original_data = table(original, &tag);
+        match &tag {
+            b"glyf" | b"loca" => {}
+            b"head" => {
+                assert_eq!([&original_data[..8], &original_data[12..]], [&data[..8], &data[12..]])
+            }
+            _ => assert_eq!(original_data, data, "table {} differs", String::from_utf8_lossy(&tag)),
+        }
+    }
+    assert_eq!(table(original, b"loca").len(), table(decoded, b"loca").len());
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |acc, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        acc.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+#[test]
+fn test_decode_roundtrip_without_transform() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode_no_transform(&ttf_data, BrotliQuality::from(5)).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+
+    let original = sfnt_tables(&ttf_data);
+    let roundtripped = sfnt_tables(&decoded);
+    assert_eq!(&decoded[0..4], &ttf_data[0..4]);
+    assert_eq!(original.len(), roundtripped.len());
+
+    for (tag, _, data) in &original {
+        let (_, _, decoded_data) = roundtripped
+            .iter()
+            .find(|(t, ..)| t == tag)
+            .unwrap_or_else(|| panic!("missing table {}", String::from_utf8_lossy(tag)));
+        if tag == b"head" {
+            // checkSumAdjustment is recomputed for the new layout
+            assert_eq!(&data[..8], &decoded_data[..8]);
+            assert_eq!(&data[12..], &decoded_data[12..]);
+        } else {
+            assert_eq!(data, decoded_data, "table {} differs", String::from_utf8_lossy(tag));
+        }
+    }
+}
+
+#[test]
+fn test_decoded_checksums_are_valid() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode_no_transform(&ttf_data, BrotliQuality::from(5)).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+
+    for (tag, recorded, data) in sfnt_tables(&decoded) {
+        let actual = if &tag == b"head" {
+            let mut head = data.to_vec();
+            head[8..12].fill(0);
+            checksum(&head)
+        } else {
+            checksum(data)
+        };
+        assert_eq!(recorded, actual, "checksum of {}", String::from_utf8_lossy(&tag));
+    }
+    assert_eq!(checksum(&decoded), 0xB1B0AFBA);
+}
+
+#[test]
+fn test_decode_rejects_bad_signature() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    assert!(decode(&ttf_data).is_err());
+}
+
+#[test]
+fn test_decode_rejects_truncated_data() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode_no_transform(&ttf_data, BrotliQuality::from(5)).unwrap();
+    assert!(decode(&woff2_data[..woff2_data.len() / 2]).is_err());
+    assert!(decode(&woff2_data[..20]).is_err());
+}
+
+#[test]
+fn test_decode_rejects_huge_declared_length() {
+    // A single `name` table (known tag 5, null transform) whose origLength is 0xFFFFFFFF, followed
+    // by one byte of font data
+    let directory = [0x05, 0x8F, 0xFF, 0xFF, 0xFF, 0x7F];
+    let mut woff2_data = b"wOF2".to_vec();
+    woff2_data.extend_from_slice(&0x00010000u32.to_be_bytes());
+    woff2_data.extend_from_slice(&(48 + directory.len() as u32 + 1).to_be_bytes());
+    woff2_data.extend_from_slice(&[0, 1, 0, 0]);
+    woff2_data.extend_from_slice(&u32::MAX.to_be_bytes());
+    woff2_data.extend_from_slice(&1u32.to_be_bytes());
+    woff2_data.extend_from_slice(&[0; 24]);
+    woff2_data.extend_from_slice(&directory);
+    woff2_data.push(0);
+
+    assert!(matches!(decode(&woff2_data), Err(Error::InvalidWoff2(_))));
+}
+
+#[test]
+fn test_decode_transformed_glyf() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+    assert_eq!(sfnt_tables(&ttf_data).len(), sfnt_tables(&decoded).len());
+    assert_same_font_data(&ttf_data, &decoded);
+    assert_eq!(checksum(&decoded), 0xB1B0AFBA);
+}
+
+#[test]
+fn test_decode_transformed_hmtx() {
+    let ttf_data = read_fixture("Recursive_VF_1.085.ttf");
+    let quality = BrotliQuality::from(5);
+    let options = EncodeOptions {
+        quality,
+        transform_hmtx: true,
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+    assert!(woff2_data.len() < encode(&ttf_data, quality).unwrap().len());
+
+    let decoded = decode(&woff2_data).unwrap();
+    assert_same_font_data(&ttf_data, &decoded);
+    assert_eq!(table(&ttf_data, b"hmtx"), table(&decoded, b"hmtx"));
+}
+
+#[test]
+fn test_decode_is_stable_across_reencoding() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let decoded = decode(&encode(&ttf_data, BrotliQuality::from(5)).unwrap()).unwrap();
+    let redecoded = decode(&encode(&decoded, BrotliQuality::from(5)).unwrap()).unwrap();
+    assert_eq!(table(&decoded, b"glyf"), table(&redecoded, b"glyf"));
+    assert_eq!(table(&decoded, b"loca"), table(&redecoded, b"loca"));
+}
+
+#[test]
+fn test_decode_fonttools_golden() {
+    for name in ["WarpnineSans-Regular", "Recursive_VF_1.085"] {
+        let ttf_data = read_fixture(&format!("{name}.ttf"));
+        let decoded_golden = decode(&read_fixture(&format!("golden/{name}.woff2"))).unwrap();
+        let decoded_ours = decode(&encode(&ttf_data, BrotliQuality::from(5)).unwrap()).unwrap();
+
+        // fonttools drops DSIG and sets head.flags bit 11 and head.modified when writing WOFF2,
+        // so head is excluded and only the tables present in the golden files are compared.
+        for (tag, _, data) in sfnt_tables(&decoded_golden) {
+            if &tag != b"head" {
+                assert_eq!(
+                    table(&decoded_ours, &tag),
+                    data,
+                    "{name}: {}",
+                    String::from_utf8_lossy(&tag)
+                );
+            }
+        }
+    }
+}
+
+/// Reads a UIntBase128 at `pos`, returning the value and the position after it.
+fn base128(data: &[u8], mut pos: usize) -> (u32, usize) {
+    let mut value = 0;
+    loop {
+        let byte = data[pos];
+        pos += 1;
+        value = (value << 7) | u32::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return (value, pos);
+        }
+    }
+}
+
+/// Replaces the table directory entry of the (transformed) loca table with `entry`, keeping the
+/// header's length and table count in step.
+fn replace_loca_entry(woff2_data: &[u8], entry: &[u8]) -> Vec<u8> {
+    let num_tables = u16::from_be_bytes([woff2_data[12], woff2_data[13]]);
+    let mut pos = 48;
+    for _ in 0..num_tables {
+        let start = pos;
+        let flags = woff2_data[pos];
+        pos += if flags & 0x3F == 63 { 5 } else { 1 };
+        pos = base128(woff2_data, pos).1;
+        let (tag_index, version) = (flags & 0x3F, flags >> 6);
+        let transformed =
+            if tag_index == 10 || tag_index == 11 { version == 0 } else { version != 0 };
+        if transformed {
+            pos = base128(woff2_data, pos).1;
+        }
+        if tag_index == 11 {
+            assert_eq!(version, 0);
+            let mut data = woff2_data[..start].to_vec();
+            data.extend_from_slice(entry);
+            data.extend_from_slice(&woff2_data[pos..]);
+            let length = data.len() as u32;
+            data[8..12].copy_from_slice(&length.to_be_bytes());
+            let num_tables = num_tables - u16::from(entry.is_empty());
+            data[12..14].copy_from_slice(&num_tables.to_be_bytes());
+            return data;
+        }
+    }
+    panic!("no loca table");
+}
+
+#[test]
+fn test_decode_rejects_transformed_glyf_without_loca() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+
+    // A transformed loca stores no data, so dropping its entry leaves the font data intact
+    let without_loca = replace_loca_entry(&woff2_data, &[]);
+    assert!(matches!(
+        decode(&without_loca),
+        Err(Error::InvalidWoff2("transformed glyf without loca"))
+    ));
+}
+
+#[test]
+fn test_decode_rejects_transformed_glyf_with_untransformed_loca() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+
+    // An empty loca with the null transform (version 3), in place of the transformed one
+    let null_loca = replace_loca_entry(&woff2_data, &[0xC0 | 11, 0]);
+    assert!(matches!(
+        decode(&null_loca),
+        Err(Error::InvalidWoff2("transformed glyf with untransformed loca"))
+    ));
+}
@@ -1,6 +1,10 @@
-use std::{fs::read, path::PathBuf};
+use std::{
+    fs::read,
+    io::{self, Write},
+    path::PathBuf,
+};
 
-use ttf2woff2::{BrotliQuality, encode};
+use ttf2woff2::{BrotliQuality, EncodeOptions, Error, encode, encode_to, encode_with_options};
 
 fn fixtures_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
@@ -59,3 +63,42 @@ fn test_noto_sans_jp() {
 fn test_recursive_vf() {
     test_font("Recursive_VF_1.085");
 }
+
+#[test]
+fn test_encode_to_matches_encode_with_options() {
+    let ttf_data = read(fixtures_dir().join("WarpnineSans-Regular.ttf")).unwrap();
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some("<metadata version=\"1.0\"/>".to_string()),
+        private_data: Some(b"build 42".to_vec()),
+        ..EncodeOptions::default()
+    };
+    let expected = encode_with_options(&ttf_data, options.clone()).unwrap();
+
+    let mut written = Vec::new();
+    let len = encode_to(&ttf_data, options, &mut written).unwrap();
+    assert_eq!(len, written.len());
+    assert_eq!(written, expected);
+}
+
+#[test]
+fn test_encode_to_reports_write_errors() {
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let ttf_data = read(fixtures_dir().join("WarpnineSans-Regular.ttf")).unwrap();
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    };
+    assert!(matches!(encode_to(&ttf_data, options, FailingWriter), Err(Error::Io(_))));
+}
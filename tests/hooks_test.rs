@@ -0,0 +1,118 @@
+use std::{
+    fs::read,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use ttf2woff2::{
+    BrotliQuality, CancelHook, EncodeOptions, Error, Progress, ProgressHook, encode_with_options,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    }
+}
+
+#[test]
+fn test_progress_reported() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let hooked = EncodeOptions {
+        progress: Some(ProgressHook::new(move |progress| sink.lock().unwrap().push(progress))),
+        ..options()
+    };
+
+    // Hooks only observe the encode
+    assert_eq!(
+        encode_with_options(&ttf_data, hooked).unwrap(),
+        encode_with_options(&ttf_data, options()).unwrap()
+    );
+
+    let events = events.lock().unwrap();
+    let glyphs: Vec<_> = events
+        .iter()
+        .filter_map(|event| match *event {
+            Progress::Glyphs { done, total } => Some((done, total)),
+            _ => None,
+        })
+        .collect();
+    let compressed: Vec<_> = events
+        .iter()
+        .filter_map(|event| match *event {
+            Progress::Compressed { done, total } => Some((done, total)),
+            _ => None,
+        })
+        .collect();
+
+    assert!(glyphs.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    let &(done, total) = glyphs.last().unwrap();
+    assert_eq!(done, total);
+
+    assert!(compressed.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    let &(done, total) = compressed.last().unwrap();
+    assert_eq!(done, total);
+    assert!(total > 0);
+
+    // Glyphs are transformed before anything is compressed
+    let first_compressed = events
+        .iter()
+        .position(|event| matches!(event, Progress::Compressed { .. }))
+        .unwrap();
+    assert!(
+        events[first_compressed..]
+            .iter()
+            .all(|event| matches!(event, Progress::Compressed { .. }))
+    );
+}
+
+#[test]
+fn test_cancel_before_start() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        cancel: Some(CancelHook::new(|| true)),
+        ..options()
+    };
+    assert!(matches!(encode_with_options(&ttf_data, options), Err(Error::Cancelled)));
+}
+
+#[test]
+fn test_cancel_from_progress() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (flag, sink) = (Arc::clone(&cancelled), Arc::clone(&events));
+    let options = EncodeOptions {
+        progress: Some(ProgressHook::new(move |progress| {
+            sink.lock().unwrap().push(progress);
+            if matches!(progress, Progress::Compressed { .. }) {
+                flag.store(true, Ordering::Relaxed);
+            }
+        })),
+        cancel: Some(CancelHook::new(move || cancelled.load(Ordering::Relaxed))),
+        ..options()
+    };
+
+    assert!(matches!(encode_with_options(&ttf_data, options), Err(Error::Cancelled)));
+    // The encode stops at the first progress after the hook asks for it
+    let events = events.lock().unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| matches!(event, Progress::Compressed { .. }))
+            .count(),
+        1
+    );
+}
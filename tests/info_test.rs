@@ -0,0 +1,91 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{BrotliQuality, EncodeOptions, Error, Tag, Woff2Info, encode, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+#[test]
+fn test_info_describes_encoded_font() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+    let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+
+    assert_eq!(info.flavor, 0x00010000);
+    assert_eq!(info.length as usize, woff2_data.len());
+    assert!(info.total_sfnt_size as usize >= ttf_data.len() - 3);
+    assert!(info.collection.is_none());
+    assert_eq!((info.meta_length, info.priv_length), (0, 0));
+    assert_eq!((info.metadata, info.private_data), (None, None));
+
+    let num_tables = u16::from_be_bytes([ttf_data[4], ttf_data[5]]) as usize;
+    assert_eq!(info.tables.len(), num_tables);
+    let tags: Vec<String> = info.tables.iter().map(|t| t.tag.to_string()).collect();
+    let glyf = tags.iter().position(|t| t == "glyf").unwrap();
+    assert_eq!(tags[glyf + 1], "loca");
+
+    for table in &info.tables {
+        match table.tag.to_string().as_str() {
+            "glyf" => {
+                assert!(table.is_transformed());
+                assert_eq!(table.transform_version, 0);
+                assert!(table.stored_length() < table.orig_length);
+            }
+            "loca" => assert_eq!(table.transform_length, Some(0)),
+            _ => {
+                assert!(!table.is_transformed());
+                assert_eq!(table.stored_length(), table.orig_length);
+            }
+        }
+    }
+    assert_eq!(info.tables[glyf].tag, Tag::from(*b"glyf"));
+}
+
+#[test]
+fn test_info_reports_untransformed_tables_and_blocks() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        transform_glyf_loca: false,
+        metadata: Some("<metadata version=\"1.0\"/>".to_string()),
+        private_data: Some(b"build 42".to_vec()),
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+    let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+
+    let glyf = info.tables.iter().find(|t| t.tag.to_string() == "glyf").unwrap();
+    assert_eq!(glyf.transform_version, 3);
+    assert!(!glyf.is_transformed());
+    assert_eq!(info.metadata.as_deref(), Some("<metadata version=\"1.0\"/>"));
+    assert_eq!(info.private_data.as_deref(), Some(&b"build 42"[..]));
+    assert_eq!(info.priv_length, 8);
+}
+
+#[test]
+fn test_info_rejects_invalid_data() {
+    assert!(Woff2Info::try_from(&b"wOF2"[..]).is_err());
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    assert!(Woff2Info::try_from(&ttf_data[..]).is_err());
+}
+
+#[test]
+fn test_info_rejects_huge_metadata_length() {
+    // One `name` table directory entry, then a one-byte metadata block declaring 4 GiB
+    let mut woff2_data = b"wOF2".to_vec();
+    woff2_data.extend_from_slice(&0x00010000u32.to_be_bytes());
+    woff2_data.extend_from_slice(&51u32.to_be_bytes());
+    woff2_data.extend_from_slice(&[0, 1, 0, 0]);
+    woff2_data.extend_from_slice(&[0; 12]);
+    woff2_data.extend_from_slice(&50u32.to_be_bytes());
+    woff2_data.extend_from_slice(&1u32.to_be_bytes());
+    woff2_data.extend_from_slice(&u32::MAX.to_be_bytes());
+    woff2_data.extend_from_slice(&[0; 8]);
+    woff2_data.extend_from_slice(&[0x05, 0x01, 0x00]);
+
+    assert!(matches!(Woff2Info::try_from(&woff2_data[..]), Err(Error::InvalidWoff2(_))));
+}
@@ -0,0 +1,141 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{
+    BrotliQuality, EncodeOptions, Error, decode, decode_metadata, decode_private_data, encode,
+    encode_with_options,
+};
+
+const METADATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata version="1.0">
+  <vendor name="Example Foundry" url="https://example.com/"/>
+  <license url="https://example.com/license">
+    <text lang="en">Licensed to Example Corp. for web use only.</text>
+  </license>
+</metadata>
+"#;
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+#[test]
+fn test_metadata_block_layout() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some(METADATA.to_string()),
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+
+    let length = be_u32(&woff2_data, 8) as usize;
+    let total_compressed_size = be_u32(&woff2_data, 20) as usize;
+    let meta_offset = be_u32(&woff2_data, 28) as usize;
+    let meta_length = be_u32(&woff2_data, 32) as usize;
+    let meta_orig_length = be_u32(&woff2_data, 36) as usize;
+
+    assert_eq!(length, woff2_data.len());
+    assert_eq!(length % 4, 0);
+    assert_eq!(meta_offset % 4, 0);
+    assert!(meta_offset >= 48 + total_compressed_size);
+    assert!(meta_offset + meta_length <= length);
+    assert!(length - (meta_offset + meta_length) < 4);
+    assert_eq!(meta_orig_length, METADATA.len());
+    assert!(meta_length < meta_orig_length);
+
+    assert_eq!(decode_metadata(&woff2_data).unwrap().as_deref(), Some(METADATA));
+    // The font data is unaffected by the metadata block
+    let plain = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+    assert_eq!(decode(&woff2_data).unwrap(), decode(&plain).unwrap());
+}
+
+#[test]
+fn test_no_extension_blocks_by_default() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode(&ttf_data, BrotliQuality::from(5)).unwrap();
+    assert_eq!(&woff2_data[28..48], &[0; 20]);
+    assert_eq!(decode_metadata(&woff2_data).unwrap(), None);
+    assert_eq!(decode_private_data(&woff2_data).unwrap(), None);
+}
+
+#[test]
+fn test_decode_metadata_rejects_out_of_bounds_block() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some(METADATA.to_string()),
+        ..EncodeOptions::default()
+    };
+    let mut woff2_data = encode_with_options(&ttf_data, options).unwrap();
+    let length = woff2_data.len() as u32;
+    woff2_data[28..32].copy_from_slice(&length.to_be_bytes());
+    assert!(decode_metadata(&woff2_data).is_err());
+}
+
+#[test]
+fn test_decode_metadata_rejects_forged_orig_length() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some(METADATA.to_string()),
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+
+    // Beyond the decompression limit, and within it but not matching the block
+    for meta_orig_length in [u32::MAX, 20 << 20] {
+        let mut forged = woff2_data.clone();
+        forged[36..40].copy_from_slice(&meta_orig_length.to_be_bytes());
+        assert!(matches!(decode_metadata(&forged), Err(Error::InvalidWoff2(_))));
+    }
+}
+
+#[test]
+fn test_private_data_block_follows_metadata() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let private_data = b"build=1234\0source=3f2a9c".to_vec();
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some(METADATA.to_string()),
+        private_data: Some(private_data.clone()),
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+
+    let length = be_u32(&woff2_data, 8) as usize;
+    let meta_end = be_u32(&woff2_data, 28) as usize + be_u32(&woff2_data, 32) as usize;
+    let priv_offset = be_u32(&woff2_data, 40) as usize;
+    let priv_length = be_u32(&woff2_data, 44) as usize;
+
+    assert_eq!(length, woff2_data.len());
+    assert_eq!(priv_offset % 4, 0);
+    assert!(priv_offset >= meta_end && priv_offset - meta_end < 4);
+    assert_eq!(priv_length, private_data.len());
+    assert!(length - (priv_offset + priv_length) < 4);
+    assert_eq!(&woff2_data[priv_offset..priv_offset + priv_length], private_data);
+
+    assert_eq!(decode_private_data(&woff2_data).unwrap(), Some(private_data));
+    assert_eq!(decode_metadata(&woff2_data).unwrap().as_deref(), Some(METADATA));
+}
+
+#[test]
+fn test_private_data_block_without_metadata() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        quality: BrotliQuality::from(5),
+        private_data: Some(vec![0xAB; 5]),
+        ..EncodeOptions::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, options).unwrap();
+    assert_eq!(&woff2_data[28..40], &[0; 12]);
+    let priv_offset = be_u32(&woff2_data, 40) as usize;
+    assert!(priv_offset >= 48 + be_u32(&woff2_data, 20) as usize);
+    assert_eq!(decode_private_data(&woff2_data).unwrap(), Some(vec![0xAB; 5]));
+}
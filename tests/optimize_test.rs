@@ -0,0 +1,79 @@
+use std::{fs::read, num::NonZeroUsize, path::PathBuf};
+
+use ttf2woff2::{
+    BrotliBlockSize, BrotliQuality, BrotliWindow, Candidate, EncodeOptions, Error, OptimizeOptions,
+    decode, encode_optimized, encode_with_options,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+/// A small search space at low qualities, to keep the tests fast.
+fn fast_space() -> OptimizeOptions {
+    OptimizeOptions {
+        qualities: vec![BrotliQuality::from(4), BrotliQuality::from(5)],
+        lgwins: vec![BrotliWindow::try_from(18).unwrap(), BrotliWindow::default()],
+        lgblocks: vec![BrotliBlockSize::AUTO],
+        parallelism: NonZeroUsize::new(2),
+        ..OptimizeOptions::default()
+    }
+}
+
+#[test]
+fn test_optimize_keeps_smallest() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let optimized = encode_optimized(&ttf_data, EncodeOptions::default(), &fast_space()).unwrap();
+
+    assert_eq!(optimized.sizes.len(), 8);
+    let smallest = optimized.sizes.iter().map(|&(_, size)| size).min().unwrap();
+    assert_eq!(optimized.data.len(), smallest);
+    assert!(optimized.sizes.contains(&(optimized.winner, smallest)));
+    decode(&optimized.data).unwrap();
+
+    // With the default window and block size, a candidate matches a regular encode
+    let winner = optimized.winner;
+    if winner.lgwin == BrotliWindow::default() {
+        let options = EncodeOptions {
+            quality: winner.quality,
+            transform_glyf_loca: winner.transform_glyf_loca,
+            ..EncodeOptions::default()
+        };
+        assert_eq!(optimized.data, encode_with_options(&ttf_data, options).unwrap());
+    }
+}
+
+#[test]
+fn test_optimize_is_deterministic() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let parallel = encode_optimized(&ttf_data, EncodeOptions::default(), &fast_space()).unwrap();
+    let sequential = OptimizeOptions { parallelism: NonZeroUsize::new(1), ..fast_space() };
+    let sequential = encode_optimized(&ttf_data, EncodeOptions::default(), &sequential).unwrap();
+    assert_eq!(parallel.winner, sequential.winner);
+    assert_eq!(parallel.sizes, sequential.sizes);
+    assert_eq!(parallel.data, sequential.data);
+}
+
+#[test]
+fn test_optimize_empty_space() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let space = OptimizeOptions { lgwins: vec![], ..fast_space() };
+    assert!(matches!(
+        encode_optimized(&ttf_data, EncodeOptions::default(), &space),
+        Err(Error::InvalidOptions(_))
+    ));
+}
+
+#[test]
+fn test_candidate_display() {
+    let candidate = Candidate {
+        transform_glyf_loca: false,
+        quality: BrotliQuality::from(11),
+        lgwin: BrotliWindow::try_from(24).unwrap(),
+        lgblock: BrotliBlockSize::AUTO,
+    };
+    assert_eq!(candidate.to_string(), "transform off, quality 11, lgwin 24, lgblock auto");
+}
@@ -0,0 +1,102 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{BrotliQuality, decode, encode};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn sfnt_tables(font: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let offset = be_u32(font, record + 8) as usize;
+            let length = be_u32(font, record + 12) as usize;
+            (font[record..record + 4].try_into().unwrap(), font[offset..offset + length].to_vec())
+        })
+        .collect()
+}
+
+/// Builds a minimal SFNT; table checksums are left at zero since the encoder ignores them.
+fn build_sfnt(flavor: &[u8; 4], tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut font = flavor.to_vec();
+    font.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+    font.extend_from_slice(&[0; 6]);
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in tables {
+        font.extend_from_slice(data);
+        font.resize((font.len() + 3) & !3, 0);
+    }
+    font
+}
+
+/// Turns the TrueType fixture into a CFF-flavored font by replacing `glyf`/`loca` with an opaque
+/// `CFF ` table.
+fn otf_font() -> Vec<u8> {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let mut tables: Vec<_> = sfnt_tables(&ttf_data)
+        .into_iter()
+        .filter(|(tag, _)| tag != b"glyf" && tag != b"loca")
+        .collect();
+    tables.push((*b"CFF ", (0..5000u32).map(|i| (i * 7 % 251) as u8).collect()));
+    tables.sort_by_key(|(tag, _)| *tag);
+    build_sfnt(b"OTTO", &tables)
+}
+
+#[test]
+fn test_otf_flavor_is_preserved() {
+    let otf_data = otf_font();
+    let woff2_data = encode(&otf_data, BrotliQuality::from(9)).unwrap();
+    assert_eq!(&woff2_data[0..4], b"wOF2");
+    assert_eq!(&woff2_data[4..8], b"OTTO");
+}
+
+#[test]
+fn test_otf_total_sfnt_size() {
+    let otf_data = otf_font();
+    let woff2_data = encode(&otf_data, BrotliQuality::from(9)).unwrap();
+    let tables = sfnt_tables(&otf_data);
+    let expected = 12
+        + 16 * tables.len()
+        + tables.iter().map(|(_, data)| (data.len() + 3) & !3).sum::<usize>();
+    assert_eq!(be_u32(&woff2_data, 16) as usize, expected);
+}
+
+#[test]
+fn test_otf_roundtrip() {
+    let otf_data = otf_font();
+    let decoded = decode(&encode(&otf_data, BrotliQuality::from(9)).unwrap()).unwrap();
+    assert_eq!(&decoded[0..4], b"OTTO");
+
+    let decoded_tables = sfnt_tables(&decoded);
+    for (tag, data) in sfnt_tables(&otf_data) {
+        let (_, decoded_data) = decoded_tables.iter().find(|(t, _)| *t == tag).unwrap();
+        if &tag == b"head" {
+            assert_eq!(&data[..8], &decoded_data[..8]);
+        } else {
+            assert_eq!(&data, decoded_data, "table {}", String::from_utf8_lossy(&tag));
+        }
+    }
+}
+
+#[test]
+fn test_unknown_flavor_is_rejected() {
+    let mut data = otf_font();
+    data[0..4].copy_from_slice(b"true");
+    assert!(encode(&data, BrotliQuality::from(9)).is_err());
+}
@@ -0,0 +1,60 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{BrotliQuality, EncodeOptions, Woff2Info, encode_with_options, encode_with_report};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        metadata: Some("<metadata version=\"1.0\"/>".to_string()),
+        ..EncodeOptions::default()
+    }
+}
+
+#[test]
+fn test_report_matches_output() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let (woff2_data, report) = encode_with_report(&ttf_data, options()).unwrap();
+    assert_eq!(woff2_data, encode_with_options(&ttf_data, options()).unwrap());
+
+    let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+    assert_eq!(report.length as usize, woff2_data.len());
+    assert_eq!(report.total_sfnt_size, info.total_sfnt_size);
+    assert_eq!(report.total_compressed_size, info.total_compressed_size);
+    assert_eq!(report.metadata_length, info.meta_length);
+    assert_eq!(report.private_data_length, 0);
+    assert_eq!(report.tables.len(), info.tables.len());
+    for (table, table_info) in report.tables.iter().zip(&info.tables) {
+        assert_eq!(table.tag, table_info.tag);
+        assert_eq!(table.orig_length, table_info.orig_length);
+        assert_eq!(table.transform_length, table_info.transform_length);
+    }
+
+    // The estimated shares add up to the Brotli stream, give or take rounding down
+    let shares: u32 = report.tables.iter().map(|t| t.compressed_share).sum();
+    assert!(shares <= report.total_compressed_size);
+    assert!(shares + report.tables.len() as u32 >= report.total_compressed_size);
+}
+
+#[test]
+fn test_report_glyf_streams() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let (_, report) = encode_with_report(&ttf_data, options()).unwrap();
+    let glyf = report.tables.iter().find(|t| t.tag.to_string() == "glyf").unwrap();
+    let streams = glyf.glyf_streams.unwrap();
+    let total: u32 = streams.named().iter().map(|(_, size)| size).sum();
+    // The streams follow the 36-byte transformed glyf header
+    assert_eq!(36 + total, glyf.transform_length.unwrap());
+    assert!(streams.glyph > 0 && streams.flag > 0);
+    assert!(report.tables.iter().filter(|t| t.glyf_streams.is_some()).count() == 1);
+
+    let untransformed = EncodeOptions { transform_glyf_loca: false, ..options() };
+    let (_, report) = encode_with_report(&ttf_data, untransformed).unwrap();
+    assert!(report.tables.iter().all(|t| t.glyf_streams.is_none()));
+}
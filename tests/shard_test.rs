@@ -0,0 +1,84 @@
+use std::{fs::read, num::NonZeroUsize, path::PathBuf};
+
+use ttf2woff2::{EncodeOptions, ShardPlan, decode, encode_shards};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn codepoint_count(unicodes: &[std::ops::RangeInclusive<u32>]) -> usize {
+    unicodes.iter().map(|range| range.clone().count()).sum()
+}
+
+#[test]
+fn test_sequential_shards() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let plan = ShardPlan::Sequential {
+        codepoints_per_shard: NonZeroUsize::new(100).unwrap(),
+    };
+    let slices = plan.slices(&ttf_data).unwrap();
+    assert!(slices.len() > 1);
+
+    let shards = encode_shards(&ttf_data, &plan, EncodeOptions::default()).unwrap();
+    assert_eq!(
+        shards.iter().map(|shard| &shard.unicodes).collect::<Vec<_>>(),
+        slices.iter().collect::<Vec<_>>()
+    );
+    for (i, shard) in shards.iter().enumerate() {
+        let count = codepoint_count(&shard.unicodes);
+        assert!(count <= 100);
+        if i + 1 < shards.len() {
+            assert_eq!(count, 100);
+        }
+        decode(&shard.data).unwrap();
+    }
+
+    // Shards cover disjoint, ascending code points
+    let all: Vec<u32> = shards
+        .iter()
+        .flat_map(|shard| shard.unicodes.iter().cloned().flatten())
+        .collect();
+    assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_explicit_shards_drop_uncovered_code_points() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    // The second slice lies in the Private Use Area, which the font does not cover
+    let plan = ShardPlan::Explicit(vec![vec![0x41..=0x5A, 0x61..=0x7A], vec![0xF0000..=0xF0010]]);
+    let shards = encode_shards(&ttf_data, &plan, EncodeOptions::default()).unwrap();
+    assert_eq!(shards.len(), 1);
+    assert_eq!(shards[0].unicodes, [0x41..=0x5A, 0x61..=0x7A]);
+}
+
+#[test]
+fn test_frequency_shards() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let per_shard = NonZeroUsize::new(100).unwrap();
+    let slices = ShardPlan::Frequency { codepoints_per_shard: per_shard }
+        .slices(&ttf_data)
+        .unwrap();
+    let counts: Vec<_> = slices.iter().map(|unicodes| codepoint_count(unicodes)).collect();
+    assert!(counts[..counts.len() - 1].iter().all(|&count| count == 100));
+
+    // ASCII comes first, and the shards cover the same characters as in code point order
+    assert!(
+        slices[0]
+            .iter()
+            .any(|range| range.contains(&0x20) && range.contains(&0x7E))
+    );
+    let mut all: Vec<u32> = slices.iter().flatten().cloned().flatten().collect();
+    all.sort_unstable();
+    let sequential: Vec<u32> = ShardPlan::Sequential { codepoints_per_shard: per_shard }
+        .slices(&ttf_data)
+        .unwrap()
+        .iter()
+        .flatten()
+        .cloned()
+        .flatten()
+        .collect();
+    assert_eq!(all, sequential);
+}
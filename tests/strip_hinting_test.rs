@@ -0,0 +1,91 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{BrotliQuality, EncodeOptions, SubsetSpec, decode, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let num_tables = be_u16(font, 4) as usize;
+    (0..num_tables).map(|i| 12 + 16 * i).find_map(|record| {
+        (&font[record..record + 4] == tag).then(|| {
+            let offset = be_u32(font, record + 8) as usize;
+            let length = be_u32(font, record + 12) as usize;
+            &font[offset..offset + length]
+        })
+    })
+}
+
+fn strip_hinting_options() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        strip_hinting: true,
+        ..EncodeOptions::default()
+    }
+}
+
+fn assert_unhinted(font: &[u8]) {
+    for tag in [b"cvar", b"cvt ", b"fpgm", b"hdmx", b"LTSH", b"prep", b"VDMX"] {
+        assert!(table(font, tag).is_none(), "{} kept", String::from_utf8_lossy(tag));
+    }
+    let maxp = table(font, b"maxp").unwrap();
+    assert_eq!(be_u16(maxp, 14), 1, "maxZones");
+    assert!((16..28).step_by(2).all(|pos| be_u16(maxp, pos) == 0));
+    let head = table(font, b"head").unwrap();
+    assert_eq!(be_u16(head, 16) & 0x0014, 0, "head flags");
+}
+
+#[test]
+fn test_strip_hinting_roundtrip() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    assert!(table(&ttf_data, b"prep").is_some());
+
+    let woff2_data = encode_with_options(&ttf_data, strip_hinting_options()).unwrap();
+
+    let decoded = decode(&woff2_data).unwrap();
+    assert_unhinted(&decoded);
+    assert_eq!(
+        be_u16(table(&decoded, b"maxp").unwrap(), 4),
+        be_u16(table(&ttf_data, b"maxp").unwrap(), 4)
+    );
+    // Tables unrelated to hinting are carried over unchanged
+    for tag in [b"GSUB", b"GPOS", b"cmap", b"hmtx", b"name"] {
+        assert_eq!(table(&decoded, tag), table(&ttf_data, tag));
+    }
+}
+
+#[test]
+fn test_strip_hinting_variable_font() {
+    let ttf_data = read_fixture("Recursive_VF_1.085.ttf");
+    let woff2_data = encode_with_options(&ttf_data, strip_hinting_options()).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+    assert_unhinted(&decoded);
+    assert_eq!(table(&decoded, b"gvar"), table(&ttf_data, b"gvar"));
+}
+
+#[test]
+fn test_strip_hinting_after_subset() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let options = EncodeOptions {
+        subset: Some(SubsetSpec {
+            unicodes: vec![0x41..=0x5A],
+            ..SubsetSpec::default()
+        }),
+        ..strip_hinting_options()
+    };
+    let decoded = decode(&encode_with_options(&ttf_data, options).unwrap()).unwrap();
+    assert_unhinted(&decoded);
+    assert!(be_u16(table(&decoded, b"maxp").unwrap(), 4) < 40);
+}
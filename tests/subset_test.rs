@@ -0,0 +1,92 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{EncodeOptions, Error, SubsetSpec, decode, encode_with_options};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let num_tables = be_u16(font, 4) as usize;
+    (0..num_tables).map(|i| 12 + 16 * i).find_map(|record| {
+        (&font[record..record + 4] == tag).then(|| {
+            let offset = be_u32(font, record + 8) as usize;
+            let length = be_u32(font, record + 12) as usize;
+            &font[offset..offset + length]
+        })
+    })
+}
+
+fn num_glyphs(font: &[u8]) -> u16 {
+    be_u16(table(font, b"maxp").unwrap(), 4)
+}
+
+fn subset_options(spec: SubsetSpec) -> EncodeOptions {
+    EncodeOptions { subset: Some(spec), ..EncodeOptions::default() }
+}
+
+#[test]
+fn test_subset_roundtrip() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let full = encode_with_options(&ttf_data, EncodeOptions::default()).unwrap();
+
+    let spec = SubsetSpec {
+        unicodes: vec![0x20..=0x7E],
+        ..SubsetSpec::default()
+    };
+    let woff2_data = encode_with_options(&ttf_data, subset_options(spec)).unwrap();
+    assert!(woff2_data.len() < full.len() / 2, "{} vs {}", woff2_data.len(), full.len());
+
+    let decoded = decode(&woff2_data).unwrap();
+    let glyphs = num_glyphs(&decoded);
+    assert!(glyphs > 95 && glyphs < num_glyphs(&ttf_data), "{glyphs} glyphs");
+
+    // Layout tables refer to glyph IDs and are dropped; the metrics follow the glyph count
+    assert!(table(&decoded, b"GSUB").is_none());
+    assert!(table(&decoded, b"GPOS").is_none());
+    let hhea = table(&decoded, b"hhea").unwrap();
+    let num_h_metrics = be_u16(hhea, 34) as usize;
+    let hmtx = table(&decoded, b"hmtx").unwrap();
+    assert_eq!(hmtx.len(), 4 * num_h_metrics + 2 * (glyphs as usize - num_h_metrics));
+    assert_eq!(be_u32(table(&decoded, b"post").unwrap(), 0), 0x00030000);
+    assert_eq!(table(&decoded, b"name"), table(&ttf_data, b"name"));
+}
+
+#[test]
+fn test_subset_by_glyph_ids() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let spec = SubsetSpec { glyph_ids: vec![0..=9], ..SubsetSpec::default() };
+    let options = EncodeOptions { transform_glyf_loca: false, ..subset_options(spec) };
+    let decoded = decode(&encode_with_options(&ttf_data, options).unwrap()).unwrap();
+    assert!(num_glyphs(&decoded) >= 10);
+}
+
+#[test]
+fn test_subset_empty_spec_keeps_notdef() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff2_data = encode_with_options(&ttf_data, subset_options(SubsetSpec::default())).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+    assert_eq!(num_glyphs(&decoded), 1);
+}
+
+#[test]
+fn test_subset_rejects_variable_fonts() {
+    let ttf_data = read_fixture("Recursive_VF_1.085.ttf");
+    let spec = SubsetSpec {
+        unicodes: vec![0x41..=0x5A],
+        ..SubsetSpec::default()
+    };
+    let err = encode_with_options(&ttf_data, subset_options(spec)).unwrap_err();
+    assert!(matches!(err, Error::Subset(_)), "{err:?}");
+}
@@ -0,0 +1,74 @@
+use std::{fs::read, path::PathBuf};
+
+use ttf2woff2::{
+    BrotliQuality, EncodeOptions, Error, TableFilter, Tag, Woff2Info, decode, encode_with_options,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+fn tags(font: &[u8]) -> Vec<String> {
+    let num_tables = be_u16(font, 4) as usize;
+    (0..num_tables)
+        .map(|i| String::from_utf8_lossy(&font[12 + 16 * i..][..4]).into_owned())
+        .collect()
+}
+
+fn filter_options(filter: TableFilter) -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        table_filter: Some(filter),
+        ..EncodeOptions::default()
+    }
+}
+
+fn tag(s: &str) -> Tag {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_drop_tables() {
+    let ttf_data = read_fixture("Recursive_VF_1.085.ttf");
+    let unfiltered = encode_with_options(&ttf_data, filter_options(TableFilter::Drop(vec![])));
+    let unfiltered = Woff2Info::try_from(&unfiltered.unwrap()[..]).unwrap();
+
+    let filter = TableFilter::Drop(vec![tag("DSIG"), tag("LTSH")]);
+    let woff2_data = encode_with_options(&ttf_data, filter_options(filter)).unwrap();
+    let info = Woff2Info::try_from(&woff2_data[..]).unwrap();
+    assert_eq!(info.tables.len(), unfiltered.tables.len() - 1);
+    assert!(info.tables.iter().all(|table| table.tag != tag("DSIG")));
+
+    // totalSfntSize shrinks by the DSIG table record and its padded data
+    let dsig = unfiltered.tables.iter().find(|t| t.tag == tag("DSIG")).unwrap();
+    let dsig_size = 16 + dsig.orig_length.next_multiple_of(4);
+    assert_eq!(info.total_sfnt_size, unfiltered.total_sfnt_size - dsig_size);
+
+    let decoded = decode(&woff2_data).unwrap();
+    assert!(!tags(&decoded).contains(&"DSIG".to_string()));
+}
+
+#[test]
+fn test_keep_tables() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    // In table record order, which sorts uppercase before lowercase
+    let keep = ["OS/2", "cmap", "glyf", "head", "hhea", "hmtx", "loca", "maxp", "name", "post"];
+    let filter = TableFilter::Keep(keep.iter().map(|s| tag(s)).collect());
+    let woff2_data = encode_with_options(&ttf_data, filter_options(filter)).unwrap();
+    let decoded = decode(&woff2_data).unwrap();
+    assert_eq!(tags(&decoded), keep);
+}
+
+#[test]
+fn test_filter_keeping_nothing() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let filter = TableFilter::Keep(vec![tag("DSIG")]);
+    assert!(matches!(encode_with_options(&ttf_data, filter_options(filter)), Err(Error::NoTables)));
+}
@@ -0,0 +1,210 @@
+use std::{fs::read, io::Read, path::PathBuf};
+
+use flate2::read::ZlibDecoder;
+use ttf2woff2::{
+    BrotliQuality, EncodeOptions, Error, decode, decode_metadata, decode_private_data,
+    encode_with_options, encode_woff1,
+};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    read(path).expect("Failed to read test font")
+}
+
+fn be_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+fn be_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn inflate(data: &[u8]) -> Vec<u8> {
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut inflated).unwrap();
+    inflated
+}
+
+/// Returns `(tag, checksum, data)` for every table record of an SFNT font.
+fn sfnt_tables(ttf: &[u8]) -> Vec<([u8; 4], u32, Vec<u8>)> {
+    (0..be_u16(ttf, 4) as usize)
+        .map(|i| {
+            let record = 12 + 16 * i;
+            let offset = be_u32(ttf, record + 8) as usize;
+            let length = be_u32(ttf, record + 12) as usize;
+            let tag = ttf[record..record + 4].try_into().unwrap();
+            (tag, be_u32(ttf, record + 4), ttf[offset..offset + length].to_vec())
+        })
+        .collect()
+}
+
+/// Returns `(tag, checksum, data)` for every table of a WOFF file, inflating compressed tables.
+fn woff_tables(woff: &[u8]) -> Vec<([u8; 4], u32, Vec<u8>)> {
+    (0..be_u16(woff, 12) as usize)
+        .map(|i| {
+            let entry = 44 + 20 * i;
+            let offset = be_u32(woff, entry + 4) as usize;
+            let comp_length = be_u32(woff, entry + 8) as usize;
+            let orig_length = be_u32(woff, entry + 12) as usize;
+            assert_eq!(offset % 4, 0);
+            let stored = &woff[offset..offset + comp_length];
+            let data = if comp_length < orig_length { inflate(stored) } else { stored.to_vec() };
+            assert_eq!(data.len(), orig_length);
+            let tag = woff[entry..entry + 4].try_into().unwrap();
+            (tag, be_u32(woff, entry + 16), data)
+        })
+        .collect()
+}
+
+#[test]
+fn test_woff1_tables_roundtrip() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff_data = encode_woff1(&ttf_data, EncodeOptions::default()).unwrap();
+
+    assert_eq!(&woff_data[0..4], b"wOFF");
+    assert_eq!(be_u32(&woff_data, 4), be_u32(&ttf_data, 0));
+    assert_eq!(be_u32(&woff_data, 8) as usize, woff_data.len());
+    assert_eq!(be_u32(&woff_data, 16) as usize, ttf_data.len());
+    assert!(woff_data.len() < ttf_data.len());
+
+    let mut expected = sfnt_tables(&ttf_data);
+    expected.sort_by_key(|&(tag, ..)| tag);
+    assert_eq!(woff_tables(&woff_data), expected);
+}
+
+#[test]
+fn test_woff1_metadata_and_private_data() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let xml = "<metadata version=\"1.0\"><uniqueid id=\"test\"/></metadata>";
+    let options = EncodeOptions {
+        metadata: Some(xml.to_string()),
+        private_data: Some(b"build 42".to_vec()),
+        ..EncodeOptions::default()
+    };
+    let woff_data = encode_woff1(&ttf_data, options).unwrap();
+
+    let meta_offset = be_u32(&woff_data, 24) as usize;
+    let meta_length = be_u32(&woff_data, 28) as usize;
+    assert_eq!(meta_offset % 4, 0);
+    assert_eq!(be_u32(&woff_data, 32) as usize, xml.len());
+    assert_eq!(inflate(&woff_data[meta_offset..meta_offset + meta_length]), xml.as_bytes());
+
+    let priv_offset = be_u32(&woff_data, 36) as usize;
+    assert_eq!(priv_offset % 4, 0);
+    assert_eq!(be_u32(&woff_data, 40), 8);
+    assert_eq!(&woff_data[priv_offset..], b"build 42");
+}
+
+#[test]
+fn test_woff1_rejects_collection() {
+    // Wrap the font in a single-font collection, shifting its table offsets past the header
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let mut ttc = b"ttcf".to_vec();
+    ttc.extend_from_slice(&0x00010000u32.to_be_bytes());
+    ttc.extend_from_slice(&1u32.to_be_bytes());
+    ttc.extend_from_slice(&16u32.to_be_bytes());
+    ttc.extend_from_slice(&ttf_data);
+    for i in 0..be_u16(&ttf_data, 4) as usize {
+        let record = 16 + 12 + 16 * i;
+        let offset = be_u32(&ttc, record + 8) + 16;
+        ttc[record + 8..record + 12].copy_from_slice(&offset.to_be_bytes());
+    }
+
+    assert!(matches!(
+        encode_woff1(&ttc, EncodeOptions::default()),
+        Err(Error::CollectionNotSupported)
+    ));
+}
+
+fn fast() -> EncodeOptions {
+    EncodeOptions {
+        quality: BrotliQuality::from(5),
+        ..EncodeOptions::default()
+    }
+}
+
+#[test]
+fn test_woff1_input_transcodes() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff_data = encode_woff1(&ttf_data, EncodeOptions::default()).unwrap();
+
+    let from_woff = decode(&encode_with_options(&woff_data, fast()).unwrap()).unwrap();
+    let from_ttf = decode(&encode_with_options(&ttf_data, fast()).unwrap()).unwrap();
+    assert_eq!(from_woff, from_ttf);
+}
+
+#[test]
+fn test_woff1_input_keeps_metadata_and_private_data() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let xml = "<metadata version=\"1.0\"><uniqueid id=\"test\"/></metadata>";
+    let options = EncodeOptions {
+        metadata: Some(xml.to_string()),
+        private_data: Some(b"build 42".to_vec()),
+        ..EncodeOptions::default()
+    };
+    let woff_data = encode_woff1(&ttf_data, options).unwrap();
+
+    let woff2_data = encode_with_options(&woff_data, fast()).unwrap();
+    assert_eq!(decode_metadata(&woff2_data).unwrap().as_deref(), Some(xml));
+    assert_eq!(decode_private_data(&woff2_data).unwrap().as_deref(), Some(&b"build 42"[..]));
+
+    // Blocks set in the options replace those of the input
+    let options = EncodeOptions { private_data: Some(b"build 43".to_vec()), ..fast() };
+    let woff2_data = encode_with_options(&woff_data, options).unwrap();
+    assert_eq!(decode_metadata(&woff2_data).unwrap().as_deref(), Some(xml));
+    assert_eq!(decode_private_data(&woff2_data).unwrap().as_deref(), Some(&b"build 43"[..]));
+}
+
+#[test]
+fn test_woff1_input_corrupt() {
+    let ttf_data = read_fixture("WarpnineSans-Regular.ttf");
+    let woff_data = encode_woff1(&ttf_data, EncodeOptions::default()).unwrap();
+
+    let truncated = &woff_data[..woff_data.len() / 2];
+    assert!(matches!(encode_with_options(truncated, fast()), Err(Error::DataTooShort { .. })));
+
+    // Flip a byte at the end of the first compressed table, corrupting its zlib checksum
+    let entry = (0..be_u16(&woff_data, 12) as usize)
+        .map(|i| 44 + 20 * i)
+        .find(|&entry| be_u32(&woff_data, entry + 8) < be_u32(&woff_data, entry + 12))
+        .unwrap();
+    let end = (be_u32(&woff_data, entry + 4) + be_u32(&woff_data, entry + 8)) as usize;
+    let mut corrupt = woff_data.clone();
+    corrupt[end - 1] ^= 0xFF;
+    assert!(matches!(encode_with_options(&corrupt, fast()), Err(Error::InvalidWoff(_))));
+
+    // A stale checksum only fails when checksums are verified
+    let mut stale = woff_data;
+    stale[entry + 16] ^= 0xFF;
+    encode_with_options(&stale, fast()).unwrap();
+    let strict = EncodeOptions { verify_checksums: true, ..fast() };
+    assert!(matches!(encode_with_options(&stale, strict), Err(Error::ChecksumMismatch { .. })));
+}
+
+#[test]
+fn test_woff1_input_rejects_huge_declared_lengths() {
+    // A single zlib-compressed table declaring a 4 GiB original length
+    let compressed = [0x78, 0xDA, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01];
+    let woff = |total_sfnt_size: u32| {
+        let mut data = b"wOFF".to_vec();
+        data.extend_from_slice(&0x00010000u32.to_be_bytes());
+        data.extend_from_slice(&(44 + 20 + compressed.len() as u32).to_be_bytes());
+        data.extend_from_slice(&[0, 1, 0, 0]);
+        data.extend_from_slice(&total_sfnt_size.to_be_bytes());
+        data.extend_from_slice(&[0; 24]);
+        data.extend_from_slice(b"name");
+        data.extend_from_slice(&64u32.to_be_bytes());
+        data.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        data.extend_from_slice(&u32::MAX.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&compressed);
+        data
+    };
+
+    for total_sfnt_size in [u32::MAX, 1024] {
+        let woff_data = woff(total_sfnt_size);
+        assert!(matches!(encode_with_options(&woff_data, fast()), Err(Error::InvalidWoff(_))));
+    }
+}